@@ -0,0 +1,205 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::{
+    DockerOutput,
+    client::{DockerClient, check},
+    fetch_containers,
+};
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    Start,
+    Stop { time: Option<u32> },
+    Restart { time: Option<u32> },
+    Kill { signal: Option<String> },
+    Remove { force: bool, volumes: bool },
+}
+
+impl Action {
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Action::Start => "start",
+            Action::Stop { .. } => "stop",
+            Action::Restart { .. } => "restart",
+            Action::Kill { .. } => "kill",
+            Action::Remove { .. } => "remove",
+        }
+    }
+
+    pub fn progressive(&self) -> &'static str {
+        match self {
+            Action::Start => "starting",
+            Action::Stop { .. } => "stopping",
+            Action::Restart { .. } => "restarting",
+            Action::Kill { .. } => "killing",
+            Action::Remove { .. } => "removing",
+        }
+    }
+
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            Action::Start => "started",
+            Action::Stop { .. } => "stopped",
+            Action::Restart { .. } => "restarted",
+            Action::Kill { .. } => "killed",
+            Action::Remove { .. } => "removed",
+        }
+    }
+
+    /// Actions that lose state and therefore ask for confirmation first.
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, Action::Kill { .. } | Action::Remove { .. })
+    }
+
+    pub async fn perform(&self, client: &DockerClient, id: &str) -> Result<(), String> {
+        let request = match self {
+            Action::Start => client.post(&format!("/containers/{}/start", id)),
+            Action::Stop { time } => client
+                .post(&format!("/containers/{}/stop", id))
+                .query(&[("t", time)]),
+            Action::Restart { time } => client
+                .post(&format!("/containers/{}/restart", id))
+                .query(&[("t", time)]),
+            Action::Kill { signal } => client
+                .post(&format!("/containers/{}/kill", id))
+                .query(&[("signal", signal)]),
+            Action::Remove { force, volumes } => client
+                .delete(&format!("/containers/{}", id))
+                .query(&[("force", force), ("v", volumes)]),
+        };
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("failed to send request: {}", e))?;
+        // 304 means the container already was in the requested state.
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(());
+        }
+        check(response).await.map(|_| ())
+    }
+}
+
+/// Finds a container by full ID, ID prefix, name (with or without the leading slash),
+/// or a name cut short in the table (ending in `…`). Plain names have to match
+/// exactly, so `web` never picks `/web-db`.
+pub fn resolve<'a>(
+    containers: &'a [DockerOutput],
+    reference: &str,
+) -> Result<&'a DockerOutput, String> {
+    let name = reference.trim_start_matches('/');
+    let names = |d: &DockerOutput| -> Vec<String> {
+        d.names
+            .iter()
+            .map(|n| n.trim_start_matches('/').to_string())
+            .collect()
+    };
+
+    // An empty reference would prefix-match every container.
+    if name.is_empty() {
+        return Err(format!("no such container: {}", reference));
+    }
+
+    if let Some(d) = containers
+        .iter()
+        .find(|d| d.id == reference || names(d).iter().any(|n| n == name))
+    {
+        return Ok(d);
+    }
+
+    let shortened = name.strip_suffix('…').filter(|prefix| !prefix.is_empty());
+    let matches: Vec<&DockerOutput> = containers
+        .iter()
+        .filter(|d| {
+            d.id.starts_with(reference)
+                || shortened.is_some_and(|prefix| names(d).iter().any(|n| n.starts_with(prefix)))
+        })
+        .collect();
+    match matches.as_slice() {
+        [d] => Ok(d),
+        [] => Err(format!("no such container: {}", reference)),
+        _ => Err(format!(
+            "'{}' matches more than one container: {}",
+            reference,
+            matches
+                .iter()
+                .map(|d| display_name(d))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+pub fn display_name(d: &DockerOutput) -> String {
+    format!(
+        "{} ({})",
+        d.names.first().map(String::as_str).unwrap_or_default(),
+        &d.id[..d.id.len().min(12)]
+    )
+}
+
+fn confirm(prompt: &str) -> bool {
+    print!("{} [y/N] ", prompt);
+    std::io::stdout()
+        .flush()
+        .expect("Failed to write to stdout");
+
+    let mut answer = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .expect("Failed to read from stdin");
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+/// Runs `action` on every referenced container. Returns false if any of them failed.
+pub async fn run(client: &DockerClient, action: &Action, references: &[String], yes: bool) -> bool {
+    let containers = fetch_containers(client, true, &[], false).await;
+
+    let mut targets = Vec::new();
+    let mut ok = true;
+    for reference in references {
+        match resolve(&containers, reference) {
+            Ok(d) => targets.push(d),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ok = false;
+            }
+        }
+    }
+
+    if action.is_destructive() && !yes && !targets.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            eprintln!(
+                "error: refusing to {} containers without --yes when stdin is not a terminal",
+                action.verb()
+            );
+            return false;
+        }
+        let list = targets
+            .iter()
+            .map(|d| display_name(d))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !confirm(&format!("Really {} {}?", action.verb(), list)) {
+            return false;
+        }
+    }
+
+    for d in targets {
+        match action.perform(client, &d.id).await {
+            Ok(()) => println!("{} {}", action.past_tense(), display_name(d)),
+            Err(e) => {
+                eprintln!(
+                    "error: failed to {} {}: {}",
+                    action.verb(),
+                    display_name(d),
+                    e
+                );
+                ok = false;
+            }
+        }
+    }
+
+    ok
+}
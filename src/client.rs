@@ -0,0 +1,92 @@
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::context::Endpoint;
+
+#[derive(Deserialize, Debug)]
+struct ErrorResponse {
+    message: String,
+}
+
+/// Thin wrapper around a reqwest client talking to the Docker daemon, either over
+/// a unix socket or plain HTTP.
+#[derive(Debug, Clone)]
+pub struct DockerClient {
+    http: Client,
+    url: String,
+}
+
+impl DockerClient {
+    pub fn new(endpoint: &Endpoint) -> Self {
+        let builder = Client::builder();
+        let http = match &endpoint.socket {
+            Some(socket) => builder.unix_socket(socket.as_str()).build(),
+            None => builder.http1_only().build(),
+        }
+        .expect("Failed to build client");
+
+        DockerClient {
+            http,
+            url: endpoint.url.clone(),
+        }
+    }
+
+    pub fn get(&self, path: &str) -> RequestBuilder {
+        self.http.get(format!("{}{}", self.url, path))
+    }
+
+    pub fn post(&self, path: &str) -> RequestBuilder {
+        self.http.post(format!("{}{}", self.url, path))
+    }
+
+    pub fn delete(&self, path: &str) -> RequestBuilder {
+        self.http.delete(format!("{}{}", self.url, path))
+    }
+}
+
+/// Turns a non-success daemon response into the error message it carries.
+pub async fn check(response: Response) -> Result<Response, String> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    match response.json::<ErrorResponse>().await {
+        Ok(error) => Err(error.message),
+        Err(_) => Err(format!("daemon responded with {}", status)),
+    }
+}
+
+/// Reads a streaming response made of newline-delimited JSON objects, as sent by
+/// `/events` and `/containers/{id}/stats`.
+pub struct JsonLines {
+    response: Response,
+    buffer: Vec<u8>,
+}
+
+impl JsonLines {
+    pub fn new(response: Response) -> Self {
+        JsonLines {
+            response,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// The next object in the stream, skipping lines that don't parse as `T`. Returns
+    /// `None` once the stream ends.
+    pub async fn next<T: DeserializeOwned>(&mut self) -> Option<T> {
+        loop {
+            while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                if let Ok(value) = serde_json::from_slice(&line) {
+                    return Some(value);
+                }
+            }
+
+            match self.response.chunk().await {
+                Ok(Some(chunk)) => self.buffer.extend_from_slice(&chunk),
+                _ => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+#[derive(Deserialize, Debug, Default)]
+struct Config {
+    #[serde(rename = "currentContext", default)]
+    current_context: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Meta {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Endpoints", default)]
+    endpoints: HashMap<String, EndpointMeta>,
+}
+
+#[derive(Deserialize, Debug)]
+struct EndpointMeta {
+    #[serde(rename = "Host", default)]
+    host: String,
+}
+
+/// Where the daemon listens: a base URL, reached through a unix socket if set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub url: String,
+    pub socket: Option<String>,
+}
+
+impl Endpoint {
+    fn unix(socket: &str) -> Self {
+        Endpoint {
+            url: "http://localhost".to_string(),
+            socket: Some(socket.to_string()),
+        }
+    }
+
+    /// Parses a docker host such as `unix:///var/run/docker.sock` or
+    /// `tcp://10.0.0.5:2375`. Only plain HTTP is spoken over tcp, so `tls` hosts are
+    /// turned down rather than sent unencrypted requests.
+    fn parse(host: &str, tls: bool) -> Result<Self, String> {
+        if let Some(path) = host.strip_prefix("unix://") {
+            return Ok(Endpoint::unix(path));
+        }
+        if let Some(address) = host
+            .strip_prefix("tcp://")
+            .or_else(|| host.strip_prefix("http://"))
+        {
+            if tls {
+                return Err(format!(
+                    "TLS endpoints aren't supported yet ({}); forward the daemon to a unix socket or plain tcp instead",
+                    host
+                ));
+            }
+            return Ok(Endpoint {
+                url: format!("http://{}", address.trim_end_matches('/')),
+                socket: None,
+            });
+        }
+        match host.split_once("://") {
+            Some((scheme @ ("ssh" | "https" | "npipe"), _)) => Err(format!(
+                "{} endpoints aren't supported yet ({}); forward the daemon to a unix socket or plain tcp instead",
+                scheme, host
+            )),
+            _ => Err(format!("invalid docker host '{}'", host)),
+        }
+    }
+}
+
+/// The docker CLI's config directory, `DOCKER_CONFIG` or `~/.docker`.
+fn config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = dotenvy::var("DOCKER_CONFIG") {
+        return Some(PathBuf::from(dir));
+    }
+    dotenvy::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".docker"))
+}
+
+/// Whether `DOCKER_TLS_VERIFY` or `DOCKER_CERT_PATH` asks for TLS, as the docker CLI
+/// does for `DOCKER_HOST`.
+fn tls_from_env() -> bool {
+    ["DOCKER_TLS_VERIFY", "DOCKER_CERT_PATH"]
+        .iter()
+        .any(|var| dotenvy::var(var).is_ok_and(|v| !v.is_empty()))
+}
+
+fn current_context(dir: Option<&Path>) -> Option<String> {
+    let config = std::fs::read_to_string(dir?.join("config.json")).ok()?;
+    // A config the CLI can't read either isn't our business, just use the default.
+    let config: Config = serde_json::from_str(&config).unwrap_or_default();
+    Some(config.current_context).filter(|c| !c.is_empty())
+}
+
+/// Looks a context up in the CLI's store. Its directories are named after a hash of
+/// the context name, so rather than hashing, every `meta.json` is checked. TLS
+/// material is kept in a directory of the same name under `contexts/tls`.
+fn lookup(dir: Option<&Path>, name: &str) -> Result<Endpoint, String> {
+    let contexts = dir
+        .map(|d| d.join("contexts"))
+        .ok_or_else(|| format!("context '{}' not found (no docker config directory)", name))?;
+    let store = contexts.join("meta");
+
+    for entry in std::fs::read_dir(&store).into_iter().flatten().flatten() {
+        let Ok(meta) = std::fs::read_to_string(entry.path().join("meta.json")) else {
+            continue;
+        };
+        let Ok(meta) = serde_json::from_str::<Meta>(&meta) else {
+            continue;
+        };
+        if meta.name != name {
+            continue;
+        }
+        return match meta.endpoints.get("docker") {
+            Some(endpoint) if !endpoint.host.is_empty() => {
+                let tls = contexts
+                    .join("tls")
+                    .join(entry.file_name())
+                    .join("docker")
+                    .is_dir();
+                Endpoint::parse(&endpoint.host, tls)
+            }
+            _ => Err(format!("context '{}' has no docker endpoint", name)),
+        };
+    }
+    Err(format!(
+        "context '{}' not found in {}",
+        name,
+        store.display()
+    ))
+}
+
+/// Picks the daemon the same way the docker CLI does: `--context`, then `DOCKER_HOST`,
+/// then `DOCKER_CONTEXT`, then `currentContext` from the CLI config. Only when none of
+/// them names a daemon are `DOCKER_URL` and `DOCKER_UNIX` used.
+pub fn resolve(flag: Option<&str>) -> Result<Endpoint, String> {
+    let docker_host = dotenvy::var("DOCKER_HOST").ok().filter(|h| !h.is_empty());
+    let dir = config_dir();
+    let name = match flag {
+        Some(name) => Some(name.to_string()),
+        None if docker_host.is_some() => None,
+        None => dotenvy::var("DOCKER_CONTEXT")
+            .ok()
+            .filter(|c| !c.is_empty())
+            .or_else(|| current_context(dir.as_deref())),
+    };
+
+    if let Some(name) = name.as_deref().filter(|n| *n != "default") {
+        return lookup(dir.as_deref(), name);
+    }
+    if let Some(host) = docker_host {
+        return Endpoint::parse(&host, tls_from_env());
+    }
+
+    let url = dotenvy::var("DOCKER_URL").ok();
+    let unix = dotenvy::var("DOCKER_UNIX").ok();
+    if url.is_none() && unix.is_none() {
+        return Ok(Endpoint::unix(DEFAULT_SOCKET));
+    }
+    Ok(Endpoint {
+        url: url.unwrap_or("http://localhost".to_string()),
+        socket: unix
+            .or(Some(DEFAULT_SOCKET.to_string()))
+            .filter(|u| !u.is_empty()),
+    })
+}
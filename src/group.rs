@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    Args, Column, DockerOutput, PROJECT_LABEL, convert_containers, render, state_rank, theme::Theme,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Docker Compose project
+    Project,
+    /// Value of the given label
+    Label(String),
+    Image,
+    Network,
+    State,
+}
+
+impl GroupBy {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "project" => Ok(GroupBy::Project),
+            "image" => Ok(GroupBy::Image),
+            "network" => Ok(GroupBy::Network),
+            "state" => Ok(GroupBy::State),
+            _ => match value.strip_prefix("label:") {
+                Some(key) if !key.is_empty() => Ok(GroupBy::Label(key.to_string())),
+                Some(_) => Err("expected a label name after 'label:'".to_string()),
+                None => Err(format!(
+                    "unknown grouping '{}' (expected project, image, network, state or label:KEY)",
+                    value
+                )),
+            },
+        }
+    }
+
+    /// The groups a container belongs to. Containers can be attached to several
+    /// networks, so they can show up in more than one section.
+    fn keys(&self, d: &DockerOutput) -> Vec<String> {
+        match self {
+            GroupBy::Project => d
+                .label(PROJECT_LABEL)
+                .map(str::to_string)
+                .into_iter()
+                .collect(),
+            GroupBy::Label(key) => d
+                .label(key)
+                .map(|value| format!("{}={}", key, value))
+                .into_iter()
+                .collect(),
+            GroupBy::Image => vec![d.image.clone()],
+            GroupBy::Network => d
+                .network_settings
+                .iter()
+                .flat_map(|n| n.networks.keys().cloned())
+                .collect(),
+            GroupBy::State => vec![d.state.clone()],
+        }
+    }
+
+    fn fallback(&self) -> String {
+        match self {
+            GroupBy::Project => "(no project)".to_string(),
+            GroupBy::Label(key) => format!("(no {} label)", key),
+            GroupBy::Image => "(no image)".to_string(),
+            GroupBy::Network => "(no network)".to_string(),
+            GroupBy::State => "(unknown state)".to_string(),
+        }
+    }
+
+    /// Inside a section some columns only repeat its title, or have a better fit.
+    fn columns(&self, columns: &[Column]) -> Vec<Column> {
+        let repeated = match self {
+            GroupBy::Project => Some(Column::Project),
+            GroupBy::Label(_) => None,
+            GroupBy::Image => Some(Column::Image),
+            GroupBy::Network => Some(Column::Networks),
+            GroupBy::State => Some(Column::State),
+        };
+        columns
+            .iter()
+            .filter(|c| Some(**c) != repeated)
+            .map(|c| match (self, c) {
+                (GroupBy::Project, Column::Name) => Column::Service,
+                (_, c) => *c,
+            })
+            .collect()
+    }
+}
+
+/// Splits containers into sections, ordered by title (states from running to dead),
+/// with the ones that don't belong anywhere last. The order within each section is kept.
+fn group(output: Vec<DockerOutput>, by: &GroupBy) -> Vec<(String, Vec<DockerOutput>)> {
+    let mut groups: BTreeMap<String, Vec<DockerOutput>> = BTreeMap::new();
+    let mut rest = Vec::new();
+    for d in output {
+        let keys = by.keys(&d);
+        if keys.is_empty() {
+            rest.push(d);
+            continue;
+        }
+        for key in keys {
+            groups.entry(key).or_default().push(d.clone());
+        }
+    }
+
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    if *by == GroupBy::State {
+        groups.sort_by_key(|(state, _)| state_rank(state));
+    }
+    if !rest.is_empty() {
+        groups.push((by.fallback(), rest));
+    }
+    groups
+}
+
+/// Prints one titled table per group, with how many of its containers are running.
+pub fn print_grouped(args: &Args, columns: &[Column], output: Vec<DockerOutput>, by: &GroupBy) {
+    let columns = by.columns(columns);
+
+    for (i, (title, members)) in group(output, by).into_iter().enumerate() {
+        let heading = if *by == GroupBy::State {
+            format!("{} ({})", title, members.len())
+        } else {
+            let running = members.iter().filter(|d| d.state == "running").count();
+            format!("{} ({}/{} running)", title, running, members.len())
+        };
+        if i > 0 {
+            println!();
+        }
+        println!("{}", Theme::paint(args.theme.heading.as_ref(), &heading));
+
+        let containers = convert_containers(&members, !args.no_truncate);
+        println!("{}", render(args, &columns, &containers, None));
+    }
+}
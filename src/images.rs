@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tabled::{Tabled, settings::object::Cell};
+
+use crate::{
+    client::DockerClient,
+    convert_date_thingi, fetch_containers, host_name, human_size,
+    layout::{self, Fit, TableStyle},
+    output::{self, OutputFormat},
+    short_id,
+    theme::Theme,
+};
+
+#[derive(Tabled, Serialize, Debug)]
+struct Image {
+    repository: String,
+    tag: String,
+    id: String,
+    created: String,
+    size: String,
+    containers: usize,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct ImageOutput {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "RepoTags", default)]
+    repo_tags: Option<Vec<String>>,
+    #[serde(rename = "RepoDigests", default)]
+    repo_digests: Option<Vec<String>>,
+    #[serde(rename = "Created")]
+    created_at: i64,
+    #[serde(rename = "Size")]
+    size: i64,
+}
+
+/// Splits `repo:tag` at the last colon that isn't part of a registry port.
+pub fn split_tag(repo_tag: &str) -> (String, String) {
+    match repo_tag.rsplit_once(':') {
+        Some((repo, tag)) if !tag.contains('/') => (repo.to_string(), tag.to_string()),
+        _ => (repo_tag.to_string(), "<none>".to_string()),
+    }
+}
+
+pub async fn run(
+    client: &DockerClient,
+    all: bool,
+    dangling: bool,
+    truncate: bool,
+    format: OutputFormat,
+    theme: &Theme,
+    style: &TableStyle,
+) {
+    let mut query = vec![("all", all.to_string())];
+    if dangling {
+        query.push(("filters", r#"{"dangling":["true"]}"#.to_string()));
+    }
+
+    let mut output = client
+        .get("/images/json")
+        .query(&query)
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json::<Vec<ImageOutput>>()
+        .await
+        .expect("Failed to parse JSON response (are you sure the Docker daemon is running?)");
+    output.sort_by_key(|i| std::cmp::Reverse(i.created_at));
+
+    // The daemon reports -1 for the container count unless asked for (slow) disk usage,
+    // so count them from the container list instead.
+    let mut usage: HashMap<String, usize> = HashMap::new();
+    for d in fetch_containers(client, true, &[], false).await {
+        *usage.entry(d.image_id).or_default() += 1;
+    }
+
+    let mut images = Vec::new();
+    for i in &output {
+        let tags = match i.repo_tags.as_deref() {
+            Some(tags) if !tags.is_empty() => tags.iter().map(|t| split_tag(t)).collect(),
+            // Untagged images still have a repository if they were pulled by digest.
+            _ => vec![(
+                i.repo_digests
+                    .iter()
+                    .flatten()
+                    .next()
+                    .and_then(|d| d.split_once('@'))
+                    .map(|(repo, _)| repo.to_string())
+                    .unwrap_or_else(|| "<none>".to_string()),
+                "<none>".to_string(),
+            )],
+        };
+
+        for (repository, tag) in tags {
+            images.push(Image {
+                repository,
+                tag,
+                id: if truncate {
+                    short_id(&i.id)
+                } else {
+                    i.id.trim_start_matches("sha256:").to_string()
+                },
+                created: convert_date_thingi(i.created_at),
+                size: human_size(i.size),
+                containers: usage.get(&i.id).copied().unwrap_or_default(),
+            });
+        }
+    }
+
+    if format.is_structured() {
+        output::print_raw(&images, format);
+        return;
+    }
+
+    if format.is_report() {
+        let report = output::Report {
+            title: "Images".to_string(),
+            host: host_name(client).await,
+            headers: Image::headers().into_iter().map(String::from).collect(),
+            records: images
+                .iter()
+                .map(|i| i.fields().into_iter().map(String::from).collect())
+                .collect(),
+            classes: Vec::new(),
+        };
+        output::print_report(&report, format, style.stylesheet);
+        return;
+    }
+
+    if format != OutputFormat::Table {
+        output::print_records(
+            Image::headers().into_iter().map(String::from).collect(),
+            images
+                .iter()
+                .map(|i| i.fields().into_iter().map(String::from).collect()),
+            format,
+            style.header,
+        );
+        return;
+    }
+
+    let fits = [
+        Fit::Ellipsize(2),
+        Fit::Ellipsize(1),
+        Fit::Fixed,
+        Fit::Fixed,
+        Fit::Fixed,
+        Fit::Fixed,
+    ];
+    let mut table = layout::table(&images, &fits, style, truncate);
+
+    if let Some(color) = &theme.image_tag {
+        for (i, image) in images.iter().enumerate() {
+            if image.tag != "<none>" {
+                table.modify(Cell::new(i + style.first_row(), 1), color.clone());
+            }
+        }
+    }
+
+    println!("{}", table);
+}
@@ -0,0 +1,420 @@
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+use ratatui::crossterm::terminal;
+use tabled::{
+    Table, Tabled,
+    builder::Builder,
+    settings::{Padding, Style, object::Columns},
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Shrunk columns keep at least this many cells, or their header if that's wider.
+const MIN_WIDTH: usize = 8;
+
+/// Wrapped lists don't get narrower than their longest item, up to this many cells.
+const MAX_ITEM_WIDTH: usize = 20;
+
+const ELLIPSIS: &str = "…";
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleName {
+    Rounded,
+    Ascii,
+    Markdown,
+    Psql,
+    Blank,
+    Modern,
+    Sharp,
+}
+
+/// How tables are drawn, from `--style`, `--compact`, `--no-header` and `--no-css`.
+#[derive(Debug, Clone, Copy)]
+pub struct TableStyle {
+    pub name: StyleName,
+    /// No borders and two spaces between columns.
+    pub compact: bool,
+    pub header: bool,
+    /// Embed CSS in HTML reports.
+    pub stylesheet: bool,
+}
+
+impl Default for TableStyle {
+    fn default() -> Self {
+        TableStyle {
+            name: StyleName::Rounded,
+            compact: false,
+            header: true,
+            stylesheet: true,
+        }
+    }
+}
+
+impl TableStyle {
+    /// The row index of the first record once the table is built.
+    pub fn first_row(&self) -> usize {
+        usize::from(self.header)
+    }
+
+    /// The cells taken up by borders and padding in a table with `columns` columns.
+    fn overhead(&self, columns: usize) -> usize {
+        if self.compact {
+            return 2 * columns.saturating_sub(1);
+        }
+        match self.name {
+            // No outer frame, just the lines between columns.
+            StyleName::Psql | StyleName::Blank => (3 * columns).saturating_sub(1),
+            _ => 3 * columns + 1,
+        }
+    }
+
+    /// Fits the records (header first) to the terminal if `truncate` is set, leaves
+    /// out the header if it's turned off and draws the table.
+    pub fn build(&self, builder: Builder, fits: &[Fit], truncate: bool) -> Table {
+        let mut builder = builder;
+        if truncate && let Some(width) = terminal_width() {
+            builder = fit(builder, fits, width, self.overhead(fits.len()));
+        }
+        if !self.header && builder.count_records() > 0 {
+            builder.remove_record(0);
+        }
+        let mut table = builder.build();
+        self.apply(&mut table, self.header);
+        table
+    }
+
+    /// Draws the borders of an already built table. Without a header the line below
+    /// it is left out as well.
+    pub fn apply(&self, table: &mut Table, header: bool) {
+        if self.compact {
+            table.with(Style::blank());
+            table.with(Padding::new(0, 1, 0, 0));
+            table.modify(Columns::last(), Padding::zero());
+            return;
+        }
+        match (self.name, header) {
+            (StyleName::Rounded, true) => table.with(Style::rounded()),
+            (StyleName::Rounded, false) => table.with(Style::rounded().remove_horizontals()),
+            (StyleName::Ascii, _) => table.with(Style::ascii()),
+            (StyleName::Markdown, true) => table.with(Style::markdown()),
+            (StyleName::Markdown, false) => table.with(Style::markdown().remove_horizontals()),
+            (StyleName::Psql, true) => table.with(Style::psql()),
+            (StyleName::Psql, false) => table.with(Style::psql().remove_horizontals()),
+            (StyleName::Blank, _) => table.with(Style::blank()),
+            (StyleName::Modern, _) => table.with(Style::modern()),
+            (StyleName::Sharp, true) => table.with(Style::sharp()),
+            (StyleName::Sharp, false) => table.with(Style::sharp().remove_horizontals()),
+        };
+    }
+}
+
+/// How a column gives way when the table is wider than the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    /// Never shrunk, e.g. IDs and numbers.
+    Fixed,
+    /// Cut off with an ellipsis. Columns with a higher number shrink first.
+    Ellipsize(u8),
+    /// Broken over several lines, preferably after the commas of a list.
+    Wrap(u8),
+}
+
+impl Fit {
+    fn priority(self) -> Option<u8> {
+        match self {
+            Fit::Fixed => None,
+            Fit::Ellipsize(priority) | Fit::Wrap(priority) => Some(priority),
+        }
+    }
+}
+
+/// The width to fit tables into: `COLUMNS` if it's set, otherwise the size of the
+/// terminal. Output that isn't going to a terminal is left alone.
+pub fn terminal_width() -> Option<usize> {
+    if let Some(columns) = dotenvy::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse::<usize>().ok())
+        .filter(|c| *c > 0)
+    {
+        return Some(columns);
+    }
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    terminal::size().ok().map(|(columns, _)| columns as usize)
+}
+
+/// The number of terminal cells `text` takes up; wide characters such as CJK count
+/// twice. For multi-line text this is the widest line.
+pub fn display_width(text: &str) -> usize {
+    text.lines()
+        .map(UnicodeWidthStr::width)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Pads `text` with spaces to `width` cells.
+pub fn pad(text: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        text,
+        " ".repeat(width.saturating_sub(display_width(text)))
+    )
+}
+
+/// Cuts every line of `text` down to `width` cells, ending in an ellipsis when
+/// something was cut off.
+pub fn ellipsize(text: &str, width: usize) -> String {
+    text.lines()
+        .map(|line| {
+            if UnicodeWidthStr::width(line) <= width {
+                return line.to_string();
+            }
+            let mut used = ELLIPSIS.width();
+            let mut cut = String::new();
+            for grapheme in line.graphemes(true) {
+                used += grapheme.width();
+                if used > width {
+                    break;
+                }
+                cut.push_str(grapheme);
+            }
+            cut.truncate(cut.trim_end().len());
+            cut + ELLIPSIS
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Breaks `text` into lines of at most `width` cells. Lists are broken after their
+/// commas, items too long for a line between words, and words too long for a line
+/// between graphemes.
+pub fn wrap(text: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+    for source in text.lines() {
+        let mut line = String::new();
+        for item in source.split_inclusive(", ") {
+            let pieces: Vec<&str> = if item.trim_end().width() <= width {
+                vec![item]
+            } else {
+                item.split_inclusive(' ').collect()
+            };
+            for piece in pieces {
+                if !line.is_empty() && line.width() + piece.trim_end().width() > width {
+                    lines.push(line.trim_end().to_string());
+                    line.clear();
+                }
+                if piece.trim_end().width() <= width {
+                    line.push_str(piece);
+                    continue;
+                }
+                for grapheme in piece.graphemes(true) {
+                    if !line.is_empty()
+                        && !grapheme.trim().is_empty()
+                        && line.width() + grapheme.width() > width
+                    {
+                        lines.push(line.trim_end().to_string());
+                        line.clear();
+                    }
+                    line.push_str(grapheme);
+                }
+            }
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+/// Shrinks the columns of a table (header first) until it fits into `width` cells
+/// along with `overhead` cells of borders. The widest column of the highest priority gives way first,
+/// one cell at a time, so similar columns end up about the same width. If the fixed
+/// columns alone don't fit, the table stays too wide.
+fn fit(builder: Builder, fits: &[Fit], width: usize, overhead: usize) -> Builder {
+    let mut records: Vec<Vec<String>> = builder.into();
+    let columns = fits.len();
+    let natural: Vec<usize> = (0..columns)
+        .map(|c| {
+            records
+                .iter()
+                .map(|r| r.get(c).map(|cell| display_width(cell)).unwrap_or_default())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let minimum: Vec<usize> = (0..columns)
+        .map(|c| {
+            let header = records
+                .first()
+                .and_then(|h| h.get(c))
+                .map(|h| display_width(h))
+                .unwrap_or_default();
+            // Lists are only readable while most of their items fit on a line.
+            let item = match fits[c] {
+                Fit::Wrap(_) => records
+                    .iter()
+                    .skip(1)
+                    .filter_map(|r| r.get(c))
+                    .flat_map(|cell| cell.lines().flat_map(|l| l.split_inclusive(", ")))
+                    .map(|item| item.trim_end().width())
+                    .max()
+                    .unwrap_or_default()
+                    .min(MAX_ITEM_WIDTH),
+                _ => 0,
+            };
+            header.max(item).max(MIN_WIDTH).min(natural[c])
+        })
+        .collect();
+
+    let mut excess = (natural.iter().sum::<usize>() + overhead).saturating_sub(width);
+    let mut limits = natural.clone();
+    let mut priorities: Vec<u8> = fits.iter().filter_map(|f| f.priority()).collect();
+    priorities.sort_unstable();
+    priorities.dedup();
+    for priority in priorities.into_iter().rev() {
+        while excess > 0 {
+            let widest = (0..columns)
+                .filter(|&c| fits[c].priority() == Some(priority) && limits[c] > minimum[c])
+                .max_by_key(|&c| limits[c]);
+            let Some(c) = widest else {
+                break;
+            };
+            limits[c] -= 1;
+            excess -= 1;
+        }
+    }
+
+    for record in records.iter_mut().skip(1) {
+        for (c, cell) in record.iter_mut().enumerate().take(columns) {
+            if display_width(cell) <= limits[c] {
+                continue;
+            }
+            *cell = match fits[c] {
+                Fit::Wrap(_) => wrap(cell, limits[c]),
+                _ => ellipsize(cell, limits[c]),
+            };
+        }
+    }
+    Builder::from(records)
+}
+
+/// Builds a table from `rows` in the given style, fitted to the terminal unless
+/// `truncate` is off.
+pub fn table<T: Tabled>(rows: &[T], fits: &[Fit], style: &TableStyle, truncate: bool) -> Table {
+    style.build(Table::builder(rows), fits, truncate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fitted(records: &[&[&str]], fits: &[Fit], width: usize) -> Vec<Vec<String>> {
+        fit(
+            Builder::from_iter(records.iter().map(|r| r.iter().copied())),
+            fits,
+            width,
+            0,
+        )
+        .into()
+    }
+
+    #[test]
+    fn display_width_counts_cells() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("web"), 3);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("a\nlonger\nb"), 6);
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(pad("too wide", 3), "too wide");
+    }
+
+    #[test]
+    fn ellipsize_cuts_to_the_width() {
+        assert_eq!(ellipsize("", 5), "");
+        assert_eq!(ellipsize("short", 5), "short");
+        assert_eq!(ellipsize("hello world", 8), "hello w…");
+        assert_eq!(ellipsize("hello there", 7), "hello…");
+        assert_eq!(ellipsize("one\ntwo three", 5), "one\ntwo…");
+    }
+
+    #[test]
+    fn ellipsize_keeps_wide_characters_and_graphemes_whole() {
+        assert_eq!(ellipsize("日本語テキスト", 6), "日本…");
+        assert_eq!(ellipsize("日本語テキスト", 7), "日本語…");
+        assert_eq!(
+            ellipsize("e\u{301}e\u{301}e\u{301}e\u{301}", 3),
+            "e\u{301}e\u{301}…"
+        );
+        assert_eq!(ellipsize("👍🏽👍🏽👍🏽", 5), "👍🏽👍🏽…");
+    }
+
+    #[test]
+    fn wrap_breaks_lists_after_commas() {
+        assert_eq!(wrap("", 5), "");
+        assert_eq!(wrap("web, db", 10), "web, db");
+        assert_eq!(wrap("web, db, cache", 8), "web, db,\ncache");
+        assert_eq!(wrap("a, b, c", 4), "a,\nb, c");
+    }
+
+    #[test]
+    fn wrap_breaks_long_items_between_words_then_graphemes() {
+        assert_eq!(wrap("hello big world", 9), "hello big\nworld");
+        assert_eq!(wrap("abcdefghij", 4), "abcd\nefgh\nij");
+        assert_eq!(wrap("日本語テキスト", 4), "日本\n語テ\nキス\nト");
+        assert_eq!(
+            wrap("e\u{301}e\u{301}e\u{301}", 2),
+            "e\u{301}e\u{301}\ne\u{301}"
+        );
+    }
+
+    #[test]
+    fn fit_shrinks_elastic_columns_but_not_the_header() {
+        let records = fitted(
+            &[&["ID", "NAME"], &["abc", "a-very-long-container-name"]],
+            &[Fit::Fixed, Fit::Ellipsize(1)],
+            20,
+        );
+        assert_eq!(records, [["ID", "NAME"], ["abc", "a-very-long-cont…"]]);
+    }
+
+    #[test]
+    fn fit_stops_at_the_minimum_width() {
+        let records = fitted(
+            &[&["ID", "NAME"], &["abc", "a-very-long-container-name"]],
+            &[Fit::Fixed, Fit::Ellipsize(1)],
+            5,
+        );
+        assert_eq!(records[1], ["abc", "a-very-…"]);
+        let records = fitted(&[&["ID"], &["a1b2c3d4e5f6"]], &[Fit::Fixed], 2);
+        assert_eq!(records[1], ["a1b2c3d4e5f6"]);
+    }
+
+    #[test]
+    fn fit_shrinks_higher_priorities_first() {
+        let a = "x".repeat(20);
+        let b = "y".repeat(20);
+        let records = fitted(
+            &[&["A", "B"], &[&a, &b]],
+            &[Fit::Ellipsize(1), Fit::Ellipsize(2)],
+            30,
+        );
+        assert_eq!(records[1], [a, format!("{}…", "y".repeat(9))]);
+    }
+
+    #[test]
+    fn fit_wraps_lists_no_narrower_than_their_items() {
+        let records = fitted(
+            &[&["NETWORKS"], &["frontend, backend, monitoring"]],
+            &[Fit::Wrap(1)],
+            4,
+        );
+        assert_eq!(records[1], ["frontend,\nbackend,\nmonitoring"]);
+    }
+
+    #[test]
+    fn fit_handles_empty_tables() {
+        assert!(fitted(&[], &[], 10).is_empty());
+        assert_eq!(fitted(&[&["ID"]], &[Fit::Ellipsize(1)], 1), [["ID"]]);
+    }
+}
@@ -0,0 +1,461 @@
+use std::io::Write;
+
+use chrono::{DateTime, FixedOffset};
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use tokio::{sync::mpsc, task::JoinSet};
+
+use crate::{
+    actions::{display_name, resolve},
+    client::{DockerClient, check},
+    fetch_containers,
+    layout::{display_width, pad},
+    theme::Theme,
+};
+
+#[derive(Deserialize, Debug)]
+struct InspectTty {
+    #[serde(rename = "Config")]
+    config: TtyConfig,
+}
+
+#[derive(Deserialize, Debug)]
+struct TtyConfig {
+    #[serde(rename = "Tty", default)]
+    tty: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug)]
+struct Line {
+    container: usize,
+    stream: Stream,
+    timestamp: Option<DateTime<FixedOffset>>,
+    /// The timestamp exactly as the daemon sent it, for `--timestamps`.
+    raw_timestamp: String,
+    text: String,
+}
+
+/// Turns the log stream into lines. Containers without a TTY send stdout and stderr
+/// multiplexed into frames with an 8-byte header: the stream type, three bytes of
+/// padding and the big-endian payload length. Containers with a TTY send raw bytes.
+struct Demuxer {
+    tty: bool,
+    buffer: Vec<u8>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl Demuxer {
+    fn new(tty: bool) -> Self {
+        Demuxer {
+            tty,
+            buffer: Vec::new(),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8], lines: &mut Vec<(Stream, String)>) {
+        if self.tty {
+            self.stdout.extend_from_slice(chunk);
+            Self::split(&mut self.stdout, Stream::Stdout, lines);
+            return;
+        }
+
+        self.buffer.extend_from_slice(chunk);
+        while self.buffer.len() >= 8 {
+            let size = u32::from_be_bytes([
+                self.buffer[4],
+                self.buffer[5],
+                self.buffer[6],
+                self.buffer[7],
+            ]) as usize;
+            if self.buffer.len() < 8 + size {
+                break;
+            }
+
+            let frame: Vec<u8> = self.buffer.drain(..8 + size).collect();
+            // 0 is stdin, which only shows up for attached streams; treat it like stdout.
+            let (partial, stream) = match frame[0] {
+                2 => (&mut self.stderr, Stream::Stderr),
+                _ => (&mut self.stdout, Stream::Stdout),
+            };
+            partial.extend_from_slice(&frame[8..]);
+            Self::split(partial, stream, lines);
+        }
+    }
+
+    /// Flushes whatever is left once the stream ends without a trailing newline.
+    fn finish(&mut self, lines: &mut Vec<(Stream, String)>) {
+        for (partial, stream) in [
+            (std::mem::take(&mut self.stdout), Stream::Stdout),
+            (std::mem::take(&mut self.stderr), Stream::Stderr),
+        ] {
+            if !partial.is_empty() {
+                lines.push((stream, String::from_utf8_lossy(&partial).into_owned()));
+            }
+        }
+    }
+
+    fn split(partial: &mut Vec<u8>, stream: Stream, lines: &mut Vec<(Stream, String)>) {
+        while let Some(end) = partial.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = partial.drain(..=end).collect();
+            let text = String::from_utf8_lossy(&line[..end]);
+            lines.push((stream, text.trim_end_matches('\r').to_string()));
+        }
+    }
+}
+
+async fn stream_logs(
+    request: RequestBuilder,
+    tty: bool,
+    container: usize,
+    tx: mpsc::Sender<Line>,
+) -> Result<(), String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("failed to send request: {}", e))?;
+    let mut response = check(response).await?;
+
+    let mut demuxer = Demuxer::new(tty);
+    let mut lines = Vec::new();
+    loop {
+        let done = match response.chunk().await {
+            Ok(Some(chunk)) => {
+                demuxer.push(&chunk, &mut lines);
+                false
+            }
+            Ok(None) => {
+                demuxer.finish(&mut lines);
+                true
+            }
+            Err(e) => return Err(format!("log stream broke off: {}", e)),
+        };
+
+        for (stream, text) in lines.drain(..) {
+            if tx.send(to_line(container, stream, text)).await.is_err() {
+                return Ok(());
+            }
+        }
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Logs are always requested with timestamps so several containers can be merged in
+/// order; split them off again here.
+fn to_line(container: usize, stream: Stream, text: String) -> Line {
+    let (raw_timestamp, text) = match text.split_once(' ') {
+        Some((ts, rest)) if DateTime::parse_from_rfc3339(ts).is_ok() => {
+            (ts.to_string(), rest.to_string())
+        }
+        _ => (String::new(), text),
+    };
+    Line {
+        container,
+        stream,
+        timestamp: DateTime::parse_from_rfc3339(&raw_timestamp).ok(),
+        raw_timestamp,
+        text,
+    }
+}
+
+fn print_line(line: &Line, prefixes: &[String], timestamps: bool) {
+    let mut output = String::new();
+    if let Some(prefix) = prefixes.get(line.container) {
+        output.push_str(prefix);
+    }
+    if timestamps && !line.raw_timestamp.is_empty() {
+        output.push_str(&line.raw_timestamp);
+        output.push(' ');
+    }
+    output.push_str(&line.text);
+    output.push('\n');
+
+    let result = match line.stream {
+        Stream::Stdout => std::io::stdout().lock().write_all(output.as_bytes()),
+        Stream::Stderr => std::io::stderr().lock().write_all(output.as_bytes()),
+    };
+    // The reader went away (e.g. piped into `head`), nothing left to do.
+    if result.is_err() {
+        std::process::exit(0);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogOptions {
+    pub follow: bool,
+    pub since: Option<i64>,
+    pub tail: Option<u64>,
+    pub timestamps: bool,
+    pub prefix: bool,
+}
+
+/// Shows the logs of the referenced containers, or of every container matching
+/// `filters` (just the running ones if there are no filters either). Returns false if
+/// any of them couldn't be read.
+pub async fn run(
+    client: &DockerClient,
+    references: &[String],
+    filters: &[(String, String)],
+    options: LogOptions,
+    theme: &Theme,
+) -> bool {
+    let candidates = fetch_containers(client, true, filters, false).await;
+
+    let mut ok = true;
+    let mut targets = Vec::new();
+    if references.is_empty() {
+        targets.extend(
+            candidates
+                .iter()
+                .filter(|d| !filters.is_empty() || d.state == "running"),
+        );
+    }
+    for reference in references {
+        match resolve(&candidates, reference) {
+            Ok(d) => targets.push(d),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ok = false;
+            }
+        }
+    }
+    if targets.is_empty() {
+        if ok {
+            eprintln!("error: no containers to show logs for");
+        }
+        return false;
+    }
+
+    let names: Vec<String> = targets
+        .iter()
+        .map(|d| {
+            d.names
+                .first()
+                .map(|n| n.trim_start_matches('/').to_string())
+                .unwrap_or_else(|| d.id[..d.id.len().min(12)].to_string())
+        })
+        .collect();
+    let width = names
+        .iter()
+        .map(|n| display_width(n))
+        .max()
+        .unwrap_or_default();
+    let prefixes: Vec<String> = if options.prefix {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let color = match theme.prefixes.len() {
+                    0 => None,
+                    n => theme.prefixes.get(i % n),
+                };
+                format!(
+                    "{} ",
+                    Theme::paint(color, &format!("{} |", pad(name, width)))
+                )
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let (tx, mut rx) = mpsc::channel(256);
+    let mut tasks = JoinSet::new();
+    for (i, d) in targets.iter().enumerate() {
+        let tty = client
+            .get(&format!("/containers/{}/json", d.id))
+            .send()
+            .await
+            .expect("Failed to send request")
+            .json::<InspectTty>()
+            .await
+            .map(|inspect| inspect.config.tty)
+            .unwrap_or_default();
+
+        let mut query = vec![
+            ("stdout", "true".to_string()),
+            ("stderr", "true".to_string()),
+            ("timestamps", "true".to_string()),
+            ("follow", options.follow.to_string()),
+        ];
+        if let Some(since) = options.since {
+            query.push(("since", since.to_string()));
+        }
+        if let Some(tail) = options.tail {
+            query.push(("tail", tail.to_string()));
+        }
+        let request = client
+            .get(&format!("/containers/{}/logs", d.id))
+            .query(&query);
+
+        let name = display_name(d);
+        let tx = tx.clone();
+        tasks.spawn(async move {
+            stream_logs(request, tty, i, tx)
+                .await
+                .map_err(|e| format!("failed to read logs of {}: {}", name, e))
+        });
+    }
+    drop(tx);
+
+    if options.follow {
+        while let Some(line) = rx.recv().await {
+            print_line(&line, &prefixes, options.timestamps);
+        }
+    } else {
+        // Without --follow everything arrives at once, so merge the containers in order.
+        let mut lines = Vec::new();
+        while let Some(line) = rx.recv().await {
+            lines.push(line);
+        }
+        lines.sort_by_key(|l| l.timestamp);
+        for line in &lines {
+            print_line(line, &prefixes, options.timestamps);
+        }
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        if let Err(e) = res.expect("Log task panicked") {
+            eprintln!("error: {}", e);
+            ok = false;
+        }
+    }
+
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(stream: u8, payload: &str) -> Vec<u8> {
+        let mut frame = vec![stream, 0, 0, 0];
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(payload.as_bytes());
+        frame
+    }
+
+    fn demux(tty: bool, chunks: &[&[u8]]) -> Vec<(Stream, String)> {
+        let mut demuxer = Demuxer::new(tty);
+        let mut lines = Vec::new();
+        for chunk in chunks {
+            demuxer.push(chunk, &mut lines);
+        }
+        demuxer.finish(&mut lines);
+        lines
+    }
+
+    #[test]
+    fn demuxes_stdout_and_stderr() {
+        let mut stream = frame(1, "one\ntwo\n");
+        stream.extend(frame(2, "oops\n"));
+        assert_eq!(
+            demux(false, &[&stream]),
+            [
+                (Stream::Stdout, "one".to_string()),
+                (Stream::Stdout, "two".to_string()),
+                (Stream::Stderr, "oops".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reassembles_frames_split_across_chunks() {
+        let mut stream = frame(1, "hello\n");
+        stream.extend(frame(2, "world\n"));
+        // Every possible cut, including inside the 8-byte headers.
+        for cut in 0..=stream.len() {
+            let (first, second) = stream.split_at(cut);
+            assert_eq!(
+                demux(false, &[first, second]),
+                [
+                    (Stream::Stdout, "hello".to_string()),
+                    (Stream::Stderr, "world".to_string()),
+                ],
+                "cut at {}",
+                cut
+            );
+        }
+    }
+
+    #[test]
+    fn joins_lines_split_across_frames() {
+        let stream = [frame(1, "par"), frame(2, "err\n"), frame(1, "tial\r\n")].concat();
+        assert_eq!(
+            demux(false, &[&stream]),
+            [
+                (Stream::Stderr, "err".to_string()),
+                (Stream::Stdout, "partial".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_multibyte_characters_split_across_frames() {
+        let bytes = "héllo\n".as_bytes();
+        let mut stream = vec![1, 0, 0, 0, 0, 0, 0, 2];
+        stream.extend_from_slice(&bytes[..2]);
+        stream.extend([1, 0, 0, 0, 0, 0, 0, (bytes.len() - 2) as u8]);
+        stream.extend_from_slice(&bytes[2..]);
+        assert_eq!(
+            demux(false, &[&stream]),
+            [(Stream::Stdout, "héllo".to_string())]
+        );
+    }
+
+    #[test]
+    fn flushes_partial_lines_when_the_stream_ends() {
+        let stream = [frame(1, "done\nno newline"), frame(2, "last")].concat();
+        assert_eq!(
+            demux(false, &[&stream]),
+            [
+                (Stream::Stdout, "done".to_string()),
+                (Stream::Stdout, "no newline".to_string()),
+                (Stream::Stderr, "last".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn waits_for_incomplete_frames() {
+        let stream = frame(1, "hello\n");
+        let mut demuxer = Demuxer::new(false);
+        let mut lines = Vec::new();
+        demuxer.push(&stream[..10], &mut lines);
+        assert!(lines.is_empty());
+        demuxer.push(&stream[10..], &mut lines);
+        assert_eq!(lines, [(Stream::Stdout, "hello".to_string())]);
+    }
+
+    #[test]
+    fn passes_tty_output_through() {
+        assert_eq!(
+            demux(true, &[b"\x01ra", b"w\r\nline", b"\n"]),
+            [
+                (Stream::Stdout, "\u{1}raw".to_string()),
+                (Stream::Stdout, "line".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_streams_have_no_lines() {
+        assert!(demux(false, &[]).is_empty());
+        assert!(demux(false, &[b""]).is_empty());
+        assert!(demux(true, &[b""]).is_empty());
+        assert_eq!(
+            demux(false, &[&frame(1, "\n")]),
+            [(Stream::Stdout, String::new())]
+        );
+    }
+}
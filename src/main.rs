@@ -1,16 +1,399 @@
+mod actions;
+mod client;
+mod context;
+mod group;
+mod images;
+mod inspect;
+mod layout;
+mod logs;
+mod networks;
+mod output;
+mod stats;
+mod template;
+mod theme;
+mod tui;
+mod volumes;
+mod watch;
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use actions::Action;
 use chrono::TimeZone;
-use clap::{Parser, arg};
-use reqwest::Client;
-use serde::Deserialize;
-use tabled::{Table, Tabled, settings::Style};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
+use client::{DockerClient, check};
+use group::GroupBy;
+use layout::{Fit, StyleName, TableStyle};
+use output::OutputFormat;
+use serde::{Deserialize, Serialize};
+use stats::Usage;
+use tabled::{
+    builder::Builder,
+    settings::{
+        Format,
+        object::{Cell, Rows},
+    },
+};
+use template::Template;
+use theme::{ColorChoice, Theme, ThemeName};
+use tokio::{sync::Semaphore, task::JoinSet};
+use watch::Change;
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg(short, long, help = "Do not truncate output")]
     no_truncate: bool,
+    #[arg(short, long, help = "Show all containers (default shows just running)")]
+    all: bool,
+    #[arg(
+        short,
+        long = "filter",
+        value_name = "KEY=VALUE",
+        value_parser = parse_filter,
+        help = "Filter output based on conditions provided (e.g. status=exited, label=foo=bar)"
+    )]
+    filters: Vec<(String, String)>,
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Output format"
+    )]
+    output: OutputFormat,
+    #[arg(
+        long,
+        help = "Print the raw container objects from the daemon instead of the table rows (json and ndjson only)"
+    )]
+    raw: bool,
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        value_parser = Template::parse,
+        conflicts_with_all = ["output", "raw"],
+        help = "Format output using a Go-style template, e.g. 'table {{.ID}}\\t{{.Names}}'"
+    )]
+    format: Option<Template>,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = DEFAULT_COLUMNS,
+        help = "Comma-separated list of columns to show, in order (table, csv and tsv output)"
+    )]
+    columns: Vec<Column>,
+    #[arg(
+        long,
+        help = "Add CPU and memory usage columns (samples stats from every running container, which takes a moment)"
+    )]
+    with_stats: bool,
+    #[arg(
+        long,
+        help = "Only show containers failing their healthcheck, with the output of the last check"
+    )]
+    unhealthy: bool,
+    #[arg(
+        long,
+        value_enum,
+        help = "Sort containers by the given key (ascending)"
+    )]
+    sort: Option<SortKey>,
+    #[arg(short, long, requires = "sort", help = "Reverse the sort order")]
+    reverse: bool,
+    #[arg(
+        short,
+        long,
+        value_name = "GROUP",
+        value_parser = GroupBy::parse,
+        conflicts_with_all = ["output", "raw", "watch"],
+        help = "Split the table into one section per group: project, image, network, state or label:KEY"
+    )]
+    group_by: Option<GroupBy>,
+    #[arg(
+        short,
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "2",
+        value_parser = parse_interval,
+        conflicts_with_all = ["output", "raw"],
+        help = "Keep the table up to date from daemon events, redrawing every SECONDS (default 2); polls instead if the event stream drops"
+    )]
+    watch: Option<Duration>,
+    #[arg(
+        long,
+        value_enum,
+        global = true,
+        default_value_t = ColorChoice::Auto,
+        help = "When to colour the output"
+    )]
+    color: ColorChoice,
+    #[arg(
+        long = "theme",
+        value_enum,
+        global = true,
+        help = "Colour theme (defaults to FANCY_DOCKER_THEME, then default); single colours can be changed with FANCY_DOCKER_COLORS"
+    )]
+    theme_name: Option<ThemeName>,
+    #[arg(
+        long = "style",
+        value_enum,
+        global = true,
+        default_value_t = StyleName::Rounded,
+        help = "Table border style"
+    )]
+    style_name: StyleName,
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "style_name",
+        help = "Draw tables without borders, for narrow terminals"
+    )]
+    compact: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Leave out the table header, e.g. for piping into awk"
+    )]
+    no_header: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Leave the embedded stylesheet out of --output html, e.g. for wikis with their own"
+    )]
+    no_css: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Docker context to use (defaults to DOCKER_HOST, DOCKER_CONTEXT, the docker CLI's current context, then DOCKER_URL and DOCKER_UNIX)"
+    )]
+    context: Option<String>,
+    #[arg(skip)]
+    theme: Theme,
+    #[arg(skip)]
+    style: TableStyle,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    #[command(about = "Browse containers in an interactive terminal UI")]
+    Tui,
+    #[command(about = "List images")]
+    Images {
+        #[arg(
+            short,
+            long,
+            help = "Show all images (default hides intermediate images)"
+        )]
+        all: bool,
+        #[arg(short, long, help = "Only show dangling (untagged, unused) images")]
+        dangling: bool,
+        #[arg(short, long, help = "Do not truncate output")]
+        no_truncate: bool,
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table, help = "Output format")]
+        output: OutputFormat,
+    },
+    #[command(about = "Show the full configuration and state of a container")]
+    Inspect {
+        #[arg(help = "Container name or (short) ID")]
+        container: String,
+        #[arg(
+            long,
+            help = "Show the values of environment variables that look like secrets"
+        )]
+        show_secrets: bool,
+    },
+    #[command(
+        about = "Show the logs of one or more containers, interleaved with coloured name prefixes"
+    )]
+    Logs {
+        #[arg(
+            help = "Container names or (short) IDs (default: all running containers, or all matching --filter)"
+        )]
+        containers: Vec<String>,
+        #[arg(
+            long = "filter",
+            value_name = "KEY=VALUE",
+            value_parser = parse_filter,
+            help = "Show logs of the containers matching these conditions (e.g. label=com.docker.compose.project=shop)"
+        )]
+        filters: Vec<(String, String)>,
+        #[arg(short, long, help = "Keep following new output")]
+        follow: bool,
+        #[arg(
+            long,
+            value_name = "TIME",
+            value_parser = parse_since,
+            help = "Only show logs since a timestamp (e.g. 2026-10-18T10:00:00Z) or a relative time (e.g. 42m)"
+        )]
+        since: Option<i64>,
+        #[arg(
+            short = 'n',
+            long,
+            help = "Number of lines to show from the end of each log (default all)"
+        )]
+        tail: Option<u64>,
+        #[arg(short, long, help = "Show timestamps")]
+        timestamps: bool,
+        #[arg(long, help = "Do not prefix lines with the container name")]
+        no_prefix: bool,
+    },
+    #[command(about = "List networks with their subnets and attached containers")]
+    Networks {
+        #[arg(short, long, help = "Do not truncate output")]
+        no_truncate: bool,
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table, help = "Output format")]
+        output: OutputFormat,
+    },
+    #[command(about = "Show live CPU, memory, network and block I/O usage of running containers")]
+    Stats {
+        #[arg(long, help = "Print a single snapshot instead of updating live")]
+        no_stream: bool,
+    },
+    #[command(about = "List volumes with their size and the containers using them")]
+    Volumes {
+        #[arg(long, help = "Only show volumes that no container references")]
+        orphaned: bool,
+        #[arg(short, long, help = "Do not truncate output")]
+        no_truncate: bool,
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table, help = "Output format")]
+        output: OutputFormat,
+    },
+    #[command(about = "Start one or more stopped containers")]
+    Start {
+        #[arg(required = true, help = "Container names or (short) IDs")]
+        containers: Vec<String>,
+    },
+    #[command(about = "Stop one or more running containers")]
+    Stop {
+        #[arg(required = true, help = "Container names or (short) IDs")]
+        containers: Vec<String>,
+        #[arg(short, long, help = "Seconds to wait before killing the container")]
+        time: Option<u32>,
+    },
+    #[command(about = "Restart one or more containers")]
+    Restart {
+        #[arg(required = true, help = "Container names or (short) IDs")]
+        containers: Vec<String>,
+        #[arg(short, long, help = "Seconds to wait before killing the container")]
+        time: Option<u32>,
+    },
+    #[command(about = "Kill one or more running containers")]
+    Kill {
+        #[arg(required = true, help = "Container names or (short) IDs")]
+        containers: Vec<String>,
+        #[arg(short, long, help = "Signal to send (default SIGKILL)")]
+        signal: Option<String>,
+        #[arg(short, long, help = "Do not ask for confirmation")]
+        yes: bool,
+    },
+    #[command(about = "Remove one or more containers")]
+    Rm {
+        #[arg(required = true, help = "Container names or (short) IDs")]
+        containers: Vec<String>,
+        #[arg(short, long, help = "Force the removal of a running container")]
+        force: bool,
+        #[arg(
+            short,
+            long,
+            help = "Remove anonymous volumes associated with the container"
+        )]
+        volumes: bool,
+        #[arg(short, long, help = "Do not ask for confirmation")]
+        yes: bool,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Id,
+    Name,
+    Created,
+    Status,
+    Image,
+    Ports,
+}
+
+const FILTER_KEYS: &[&str] = &[
+    "ancestor",
+    "before",
+    "expose",
+    "exited",
+    "health",
+    "id",
+    "isolation",
+    "is-task",
+    "label",
+    "name",
+    "network",
+    "publish",
+    "since",
+    "status",
+    "volume",
+];
+
+fn parse_interval(interval: &str) -> Result<Duration, String> {
+    match interval.parse::<f64>() {
+        Ok(secs) if secs >= 0.1 && secs.is_finite() => Ok(Duration::from_secs_f64(secs)),
+        Ok(_) => Err("interval must be at least 0.1 seconds".to_string()),
+        Err(_) => Err(format!("expected a number of seconds, got '{}'", interval)),
+    }
+}
+
+/// Accepts a Unix timestamp, an RFC 3339 date or a duration like `90s`, `42m`, `3h` or
+/// `2d` before now, and turns it into a Unix timestamp.
+fn parse_since(since: &str) -> Result<i64, String> {
+    let invalid = || {
+        format!(
+            "expected a timestamp or a duration like 42m, got '{}'",
+            since
+        )
+    };
+    if since.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(timestamp) = since.parse::<i64>() {
+        return Ok(timestamp);
+    }
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(since) {
+        return Ok(dt.timestamp());
+    }
+
+    let (amount, seconds) = match since.char_indices().last() {
+        Some((end, 's')) => (&since[..end], 1),
+        Some((end, 'm')) => (&since[..end], 60),
+        Some((end, 'h')) => (&since[..end], 60 * 60),
+        Some((end, 'd')) => (&since[..end], 24 * 60 * 60),
+        _ => return Err(invalid()),
+    };
+    amount
+        .parse::<i64>()
+        .ok()
+        .filter(|amount| *amount >= 0)
+        .and_then(|amount| amount.checked_mul(seconds))
+        .and_then(|ago| chrono::Utc::now().timestamp().checked_sub(ago))
+        .ok_or_else(invalid)
+}
+
+fn parse_filter(filter: &str) -> Result<(String, String), String> {
+    let (key, value) = filter
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", filter))?;
+
+    if !FILTER_KEYS.contains(&key) {
+        return Err(format!(
+            "unknown filter '{}' (valid filters: {})",
+            key,
+            FILTER_KEYS.join(", ")
+        ));
+    }
+
+    Ok((key.to_string(), value.to_string()))
 }
 
-#[derive(Tabled, Debug)]
+#[derive(Serialize, Debug, Default)]
 struct Docker {
     id: String,
     image: String,
@@ -19,9 +402,120 @@ struct Docker {
     created: String,
     status: String,
     ports: String,
+    state: String,
+    created_at: i64,
+    health: Health,
+    last_check: String,
+    /// Whether any port is published on all interfaces.
+    #[serde(skip)]
+    exposed: bool,
+    labels: String,
+    mounts: String,
+    networks: String,
+    size: String,
+    restart_count: Option<u64>,
+    cpu: String,
+    memory: String,
+    project: String,
+    service: String,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Id,
+    Image,
+    Name,
+    Command,
+    Created,
+    CreatedAt,
+    Status,
+    Ports,
+    State,
+    Health,
+    LastCheck,
+    Labels,
+    Mounts,
+    Networks,
+    Size,
+    Restarts,
+    Cpu,
+    Memory,
+    Project,
+    Service,
+}
+
+const PROJECT_LABEL: &str = "com.docker.compose.project";
+const SERVICE_LABEL: &str = "com.docker.compose.service";
+
+const DEFAULT_COLUMNS: [Column; 8] = [
+    Column::Id,
+    Column::Image,
+    Column::Name,
+    Column::Command,
+    Column::Created,
+    Column::Status,
+    Column::Health,
+    Column::Ports,
+];
+
+impl Column {
+    fn header(self) -> String {
+        self.to_possible_value()
+            .expect("columns are never skipped")
+            .get_name()
+            .to_string()
+    }
+
+    fn value(self, d: &Docker) -> String {
+        match self {
+            Column::Id => d.id.clone(),
+            Column::Image => d.image.clone(),
+            Column::Name => d.name.clone(),
+            Column::Command => d.command.clone(),
+            Column::Created => d.created.clone(),
+            Column::CreatedAt => format_timestamp(d.created_at),
+            Column::Status => d.status.clone(),
+            Column::Ports => d.ports.clone(),
+            Column::State => d.state.clone(),
+            Column::Health => d.health.as_str().to_string(),
+            Column::LastCheck => d.last_check.clone(),
+            Column::Labels => d.labels.clone(),
+            Column::Mounts => d.mounts.clone(),
+            Column::Networks => d.networks.clone(),
+            Column::Size => d.size.clone(),
+            Column::Restarts => d
+                .restart_count
+                .map(|count| count.to_string())
+                .unwrap_or_default(),
+            Column::Cpu => d.cpu.clone(),
+            Column::Memory => d.memory.clone(),
+            Column::Project => d.project.clone(),
+            Column::Service => d.service.clone(),
+        }
+    }
+
+    /// Free-form text gives way first, then lists and names; short fixed-size
+    /// values are never shrunk.
+    fn fit(self) -> Fit {
+        match self {
+            Column::Command | Column::LastCheck => Fit::Ellipsize(3),
+            Column::Labels | Column::Mounts => Fit::Wrap(3),
+            Column::Ports | Column::Networks => Fit::Wrap(2),
+            Column::Image | Column::Status => Fit::Ellipsize(2),
+            Column::Name | Column::Project | Column::Service | Column::Size => Fit::Ellipsize(1),
+            Column::Id
+            | Column::Created
+            | Column::CreatedAt
+            | Column::State
+            | Column::Health
+            | Column::Restarts
+            | Column::Cpu
+            | Column::Memory => Fit::Fixed,
+        }
+    }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 #[allow(non_snake_case)]
 struct DockerOutput {
@@ -29,6 +523,8 @@ struct DockerOutput {
     id: String,
     #[serde(rename = "Image")]
     image: String,
+    #[serde(rename = "ImageID", default)]
+    image_id: String,
     #[serde(rename = "Names")]
     names: Vec<String>,
     #[serde(rename = "Command")]
@@ -37,11 +533,102 @@ struct DockerOutput {
     created_at: i64,
     #[serde(rename = "Status")]
     status: String,
+    #[serde(rename = "State")]
+    state: String,
     #[serde(rename = "Ports")]
     ports: Vec<Ports>,
+    #[serde(rename = "Labels", default)]
+    labels: Option<BTreeMap<String, String>>,
+    #[serde(rename = "Mounts", default)]
+    mounts: Vec<Mount>,
+    #[serde(rename = "NetworkSettings", default)]
+    network_settings: Option<NetworkSettings>,
+    #[serde(rename = "SizeRw", default, skip_serializing_if = "Option::is_none")]
+    size_rw: Option<i64>,
+    #[serde(
+        rename = "SizeRootFs",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    size_root_fs: Option<i64>,
+    /// Not part of the list response, only filled in from inspect when needed.
+    #[serde(
+        rename = "RestartCount",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    restart_count: Option<u64>,
+    /// Output of the most recent healthcheck, also only filled in from inspect.
+    #[serde(
+        rename = "LastHealthcheck",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    last_check: Option<String>,
+    /// Not part of the list response either, only filled in from the stats endpoint.
+    #[serde(rename = "Stats", default, skip_serializing_if = "Option::is_none")]
+    stats: Option<Usage>,
+}
+
+impl DockerOutput {
+    fn label(&self, key: &str) -> Option<&str> {
+        self.labels.as_ref()?.get(key).map(String::as_str)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct Mount {
+    #[serde(rename = "Type")]
+    mount_type: String,
+    #[serde(rename = "Name", default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(rename = "Source", default)]
+    source: String,
+    #[serde(rename = "Destination")]
+    destination: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct NetworkSettings {
+    #[serde(rename = "Networks", default)]
+    networks: BTreeMap<String, EndpointSettings>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+struct EndpointSettings {
+    #[serde(rename = "NetworkID", default)]
+    network_id: String,
+    #[serde(rename = "IPAddress", default)]
+    ip_address: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct InspectDetails {
+    #[serde(rename = "RestartCount")]
+    restart_count: u64,
+    #[serde(rename = "State")]
+    state: InspectState,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug)]
+struct InspectState {
+    #[serde(rename = "Health", default)]
+    health: Option<InspectHealth>,
+}
+
+#[derive(Deserialize, Debug)]
+struct InspectHealth {
+    #[serde(rename = "Log", default)]
+    log: Option<Vec<HealthcheckResult>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct HealthcheckResult {
+    #[serde(rename = "Output", default)]
+    output: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 struct Ports {
     #[serde(rename = "IP")]
@@ -54,58 +641,288 @@ struct Ports {
     port_type: Option<String>,
 }
 
-fn convert_date_thingi(created_at: i64) -> String {
-    let secs = if created_at.abs() > 1_000_000_000_000 {
+fn timestamp_secs(created_at: i64) -> i64 {
+    if created_at.abs() > 1_000_000_000_000 {
         created_at / 1000
     } else {
         created_at
-    };
+    }
+}
 
-    match chrono::Utc.timestamp_opt(secs, 0) {
+fn convert_date_thingi(created_at: i64) -> String {
+    match chrono::Utc.timestamp_opt(timestamp_secs(created_at), 0) {
         chrono::LocalResult::Single(dt) => chrono_humanize::HumanTime::from(dt).to_string(),
         _ => created_at.to_string(),
     }
 }
 
-async fn get_containers(truncate: bool) -> Vec<Docker> {
-    let builder = Client::builder();
-    let output: Vec<DockerOutput>;
-    let url = dotenvy::var("DOCKER_URL").unwrap_or("http://localhost".to_string());
-    let unix = dotenvy::var("DOCKER_UNIX").unwrap_or("/var/run/docker.sock".to_string());
-    if unix.is_empty() {
-        let http = builder.http1_only().build().expect("Failed to build client");
+fn format_timestamp(created_at: i64) -> String {
+    match chrono::Local.timestamp_opt(timestamp_secs(created_at), 0) {
+        chrono::LocalResult::Single(dt) => dt.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+        _ => created_at.to_string(),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Info {
+    #[serde(rename = "Name")]
+    name: String,
+}
 
-        let res = http
-            .get(format!("{}/containers/json", url))
-            .send()
-            .await
-            .expect("Failed to send request")
-            .json::<Vec<DockerOutput>>()
-            .await
-            .expect("Failed to parse JSON response (are you sure the Docker daemon is running?)");
+/// The name of the docker host for reports. Falls back to this machine's name if the
+/// daemon doesn't say.
+async fn host_name(client: &DockerClient) -> String {
+    let info = match client.get("/info").send().await {
+        Ok(response) if response.status().is_success() => response.json::<Info>().await.ok(),
+        _ => None,
+    };
+    info.map(|i| i.name)
+        .filter(|name| !name.is_empty())
+        .or_else(|| dotenvy::var("HOSTNAME").ok())
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
 
-        output = res;
-    } else {
-        let unix = builder
-            .unix_socket(dotenvy::var("DOCKER_UNIX").unwrap_or("/var/run/docker.sock".to_string()))
-            .build()
-            .expect("Failed to build client");
+async fn fetch_containers(
+    client: &DockerClient,
+    all: bool,
+    filters: &[(String, String)],
+    size: bool,
+) -> Vec<DockerOutput> {
+    match try_fetch_containers(client, all, filters, size).await {
+        Ok(containers) => containers,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Lists containers, returning the error for views that keep running while the
+/// daemon is unreachable, e.g. when it restarts.
+async fn try_fetch_containers(
+    client: &DockerClient,
+    all: bool,
+    filters: &[(String, String)],
+    size: bool,
+) -> Result<Vec<DockerOutput>, String> {
+    let mut query = vec![("all", all.to_string()), ("size", size.to_string())];
+    if !filters.is_empty() {
+        let mut map: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (key, value) in filters {
+            map.entry(key).or_default().push(value);
+        }
+        query.push((
+            "filters",
+            serde_json::to_string(&map).expect("Failed to encode filters"),
+        ));
+    }
+
+    let response = client
+        .get("/containers/json")
+        .query(&query)
+        .send()
+        .await
+        .map_err(|e| {
+            format!(
+                "failed to send request (are you sure the Docker daemon is running?): {}",
+                e.without_url()
+            )
+        })?;
+    // The daemon validates filter values itself, e.g. `status=bogus`.
+    check(response)
+        .await?
+        .json::<Vec<DockerOutput>>()
+        .await
+        .map_err(|e| format!("failed to parse the container list: {}", e))
+}
+
+/// The list endpoint doesn't report restart counts or healthcheck output, so inspect
+/// every container concurrently. Containers removed since they were listed can't be
+/// inspected any more; their cells are left blank.
+async fn fetch_details(client: &DockerClient, output: &mut [DockerOutput]) {
+    let mut tasks = JoinSet::new();
+    for (i, d) in output.iter().enumerate() {
+        let request = client.get(&format!("/containers/{}/json", d.id));
+        tasks.spawn(async move {
+            let inspect = request
+                .send()
+                .await
+                .ok()?
+                .error_for_status()
+                .ok()?
+                .json::<InspectDetails>()
+                .await
+                .ok()?;
+            let last_check = inspect
+                .state
+                .health
+                .and_then(|h| h.log)
+                .and_then(|log| log.into_iter().last())
+                .map(|check| check.output.trim().to_string());
+            Some((i, inspect.restart_count, last_check))
+        });
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        if let Some((i, count, last_check)) = res.expect("Inspect task panicked") {
+            output[i].restart_count = Some(count);
+            output[i].last_check = last_check;
+        }
+    }
+}
+
+fn wants_details(columns: &[Column]) -> bool {
+    columns.contains(&Column::Restarts) || columns.contains(&Column::LastCheck)
+}
+
+const STATS_CONCURRENCY: usize = 8;
+const STATS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Samples CPU and memory usage of the running containers, a few at a time. Each sample
+/// keeps the daemon busy for about a second, and a container that doesn't answer within
+/// `STATS_TIMEOUT` is left blank rather than holding up the whole table.
+async fn fetch_stats(client: &DockerClient, output: &mut [DockerOutput]) {
+    let permits = Arc::new(Semaphore::new(STATS_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (i, d) in output.iter().enumerate() {
+        if d.state != "running" {
+            continue;
+        }
+        let client = client.clone();
+        let id = d.id.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.expect("Semaphore closed");
+            let usage = tokio::time::timeout(STATS_TIMEOUT, stats::fetch_usage(&client, &id))
+                .await
+                .ok()
+                .flatten();
+            (i, usage)
+        });
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        let (i, usage) = res.expect("Stats task panicked");
+        output[i].stats = usage;
+    }
+}
+
+fn wants_stats(columns: &[Column]) -> bool {
+    columns.contains(&Column::Cpu) || columns.contains(&Column::Memory)
+}
+
+fn state_rank(state: &str) -> u8 {
+    match state {
+        "running" => 0,
+        "restarting" => 1,
+        "paused" => 2,
+        "created" => 3,
+        "removing" => 4,
+        "exited" => 5,
+        "dead" => 6,
+        _ => 7,
+    }
+}
+
+/// Sorts on the raw daemon values, so e.g. `created` uses the timestamp rather than
+/// the humanized string.
+fn sort_containers(output: &mut [DockerOutput], key: SortKey, reverse: bool) {
+    output.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Id => a.id.cmp(&b.id),
+            SortKey::Name => a.names.first().cmp(&b.names.first()),
+            SortKey::Created => a.created_at.cmp(&b.created_at),
+            SortKey::Status => state_rank(&a.state)
+                .cmp(&state_rank(&b.state))
+                .then_with(|| a.status.cmp(&b.status)),
+            SortKey::Image => a.image.cmp(&b.image),
+            // Containers without ports go last.
+            SortKey::Ports => {
+                let lowest = |d: &DockerOutput| d.ports.iter().filter_map(|p| p.private_port).min();
+                match (lowest(a), lowest(b)) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (a, b) => a.is_none().cmp(&b.is_none()),
+                }
+            }
+        };
+        if reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Health {
+    #[default]
+    None,
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+impl Health {
+    /// The list endpoint only mentions health in the free-text status, e.g.
+    /// "Up 2 hours (unhealthy)".
+    fn from_status(status: &str) -> Self {
+        if status.contains("(unhealthy)") {
+            Health::Unhealthy
+        } else if status.contains("(healthy)") {
+            Health::Healthy
+        } else if status.contains("(health: starting)") {
+            Health::Starting
+        } else {
+            Health::None
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Health::None => "",
+            Health::Starting => "starting",
+            Health::Healthy => "healthy",
+            Health::Unhealthy => "unhealthy",
+        }
+    }
+}
 
-        let res = unix
-            .get(format!("{}/containers/json", url))
-            .send()
-            .await
-            .expect("Failed to send request")
-            .json::<Vec<DockerOutput>>()
-            .await
-            .expect("Failed to parse JSON response (are you sure the Docker daemon is running?)");
+/// Serialized the way it's shown, so containers without a healthcheck are `""` in JSON
+/// just like in the table, CSV and templates.
+impl Serialize for Health {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
 
-        output = res;
+/// Formats a byte count the way the docker CLI does (SI units, 3 significant digits).
+fn human_size(bytes: i64) -> String {
+    const UNITS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size.abs() >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
     }
+    let precision = if unit == 0 || size.abs() >= 100.0 {
+        0
+    } else if size.abs() >= 10.0 {
+        1
+    } else {
+        2
+    };
+    format!("{:.*}{}", precision, size, UNITS[unit])
+}
 
+fn convert_containers(output: &[DockerOutput], truncate: bool) -> Vec<Docker> {
     let mut vec = Vec::new();
 
-    for d in &output {
+    for d in output {
         let mut ports = String::new();
         for p in &d.ports {
             let port_entry = if truncate {
@@ -151,18 +968,81 @@ async fn get_containers(truncate: bool) -> Vec<Docker> {
             d.image.split('@').next().unwrap_or(&d.image).to_string()
         };
 
+        let name = d.names[0].clone();
+        // Outside of compose the container name is the closest thing to a service name.
+        let service = d
+            .label(SERVICE_LABEL)
+            .map(str::to_string)
+            .unwrap_or_else(|| name.clone());
+
         let docker = Docker {
-            id: truncate_string(d.id.clone(), 12, truncate),
-            image: truncate_string(
-                image,
-                37,
-                truncate,
-            ),
-            name: truncate_string(d.names[0].clone(), 20, truncate),
-            command: truncate_string(d.command.clone(), 30, truncate),
+            id: if truncate {
+                short_id(&d.id)
+            } else {
+                d.id.clone()
+            },
+            image,
+            name,
+            command: d.command.clone(),
             created: convert_date_thingi(d.created_at),
             status: d.status.clone(),
             ports,
+            state: d.state.clone(),
+            created_at: d.created_at,
+            health: Health::from_status(&d.status),
+            exposed: d.ports.iter().any(|p| {
+                p.public_port.is_some() && matches!(p.ip.as_deref(), Some("0.0.0.0" | "::"))
+            }),
+            last_check: d
+                .last_check
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            project: d.label(PROJECT_LABEL).unwrap_or_default().to_string(),
+            service,
+            labels: d
+                .labels
+                .iter()
+                .flatten()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(", "),
+            mounts: d
+                .mounts
+                .iter()
+                .map(|m| m.name.clone().unwrap_or_else(|| m.source.clone()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            networks: d
+                .network_settings
+                .iter()
+                .flat_map(|n| n.networks.keys().cloned())
+                .collect::<Vec<_>>()
+                .join(", "),
+            size: match (d.size_rw, d.size_root_fs) {
+                (Some(rw), Some(root)) => {
+                    format!("{} (virtual {})", human_size(rw), human_size(root))
+                }
+                (Some(rw), None) => human_size(rw),
+                _ => String::new(),
+            },
+            restart_count: d.restart_count,
+            cpu: d
+                .stats
+                .map(|u| format!("{:.2}%", u.cpu_percent))
+                .unwrap_or_default(),
+            memory: d
+                .stats
+                .map(|u| {
+                    format!(
+                        "{} / {}",
+                        human_size(u.memory as i64),
+                        human_size(u.memory_limit as i64)
+                    )
+                })
+                .unwrap_or_default(),
         };
         vec.push(docker);
     }
@@ -170,20 +1050,436 @@ async fn get_containers(truncate: bool) -> Vec<Docker> {
     vec
 }
 
-fn truncate_string(string: String, length: usize, apply: bool) -> String {
-    if apply && string.len() > length {
-        string.chars().take(length).collect::<String>()
-    } else {
-        string
+/// The first 12 characters of an ID, like the docker CLI shows them.
+fn short_id(id: &str) -> String {
+    id.trim_start_matches("sha256:").chars().take(12).collect()
+}
+
+async fn load_containers(
+    client: &DockerClient,
+    args: &Args,
+    columns: &[Column],
+) -> Result<Vec<DockerOutput>, String> {
+    // Size and restart counts are expensive for the daemon, so only ask for them
+    // when they're shown. Structured output always includes everything.
+    let everything = args.output.is_structured();
+
+    let mut output = try_fetch_containers(
+        client,
+        args.all,
+        &args.filters,
+        everything || columns.contains(&Column::Size),
+    )
+    .await?;
+    if everything || wants_details(columns) {
+        fetch_details(client, &mut output).await;
+    }
+    if wants_stats(columns) {
+        fetch_stats(client, &mut output).await;
+    }
+    if let Some(key) = args.sort {
+        sort_containers(&mut output, key, args.reverse);
+    }
+
+    Ok(output)
+}
+
+/// Renders the table (or template lines). In watch mode `changes` holds one entry
+/// per container and adds a marker column.
+fn render(
+    args: &Args,
+    columns: &[Column],
+    containers: &[Docker],
+    changes: Option<&[Change]>,
+) -> String {
+    let mut builder = Builder::new();
+    let mut fits: Vec<Fit> = match &args.format {
+        Some(template) if template.is_table() => {
+            builder.push_record(template.headers());
+            for d in containers {
+                builder.push_record(template.cells(d));
+            }
+            // Templates can show anything, so every column gives way equally.
+            vec![Fit::Ellipsize(1); builder.count_columns()]
+        }
+        Some(template) => {
+            return containers
+                .iter()
+                .map(|d| template.render(d))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        None => {
+            builder.push_record(columns.iter().map(|c| c.header()));
+            for d in containers {
+                builder.push_record(columns.iter().map(|c| c.value(d)));
+            }
+            columns.iter().map(|c| c.fit()).collect()
+        }
+    };
+    if let Some(changes) = changes {
+        builder.insert_column(
+            0,
+            std::iter::once("").chain(changes.iter().map(|c| c.marker())),
+        );
+        fits.insert(0, Fit::Fixed);
+    }
+    let mut table = args.style.build(builder, &fits, !args.no_truncate);
+
+    let first = args.style.first_row();
+    let theme = &args.theme;
+    for (i, d) in containers.iter().enumerate() {
+        let color = match changes {
+            Some(changes) if changes[i] != Change::Unchanged => changes[i].color(theme),
+            _ => theme.state(&d.state),
+        };
+        if let Some(color) = color {
+            table.modify(Rows::one(i + first), color);
+        }
+    }
+
+    // Health, public ports and image tags get their own colour on top of the row's so
+    // they stand out. Templates can put anything into a cell, so they're left alone.
+    if args.format.is_some() {
+        return table.to_string();
     }
+    let offset = usize::from(changes.is_some());
+    let position = |column| {
+        columns
+            .iter()
+            .position(|c| *c == column)
+            .map(|i| i + offset)
+    };
+    for (i, d) in containers.iter().enumerate() {
+        if changes.is_some_and(|changes| changes[i] == Change::Removed) {
+            continue;
+        }
+        if let (Some(column), Some(color)) = (position(Column::Health), theme.health(d.health)) {
+            table.modify(Cell::new(i + first, column), color);
+        }
+        if let (Some(column), Some(color), true) =
+            (position(Column::Ports), &theme.public_port, d.exposed)
+        {
+            table.modify(Cell::new(i + first, column), color.clone());
+        }
+        if let (Some(column), Some(color)) = (position(Column::Image), &theme.image_tag) {
+            let color = color.clone();
+            table.modify(
+                Cell::new(i + first, column),
+                Format::content(move |image| match images::split_tag(image) {
+                    (repository, tag) if tag != "<none>" => {
+                        format!("{}{}", repository, color.colorize(format!(":{}", tag)))
+                    }
+                    _ => image.to_string(),
+                }),
+            );
+        }
+    }
+
+    table.to_string()
 }
 
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
+    let mut args = Args::parse();
 
-    let mut table = Table::new(get_containers(!args.no_truncate).await);
-    table.with(Style::rounded());
+    if args.raw && !args.output.is_structured() {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--raw can only be used with --output json or ndjson",
+            )
+            .exit();
+    }
+
+    args.theme = match Theme::load(args.color, args.theme_name) {
+        Ok(theme) => theme,
+        Err(e) => Args::command().error(ErrorKind::InvalidValue, e).exit(),
+    };
+    args.style = TableStyle {
+        name: args.style_name,
+        compact: args.compact,
+        header: !args.no_header,
+        stylesheet: !args.no_css,
+    };
+
+    if args.unhealthy {
+        args.filters
+            .push(("health".to_string(), "unhealthy".to_string()));
+        if !args.columns.contains(&Column::LastCheck) {
+            args.columns.push(Column::LastCheck);
+        }
+    }
 
-    println!("{}", table);
+    if args.with_stats {
+        for column in [Column::Cpu, Column::Memory] {
+            if !args.columns.contains(&column) {
+                args.columns.push(column);
+            }
+        }
+    }
+
+    let columns: Vec<Column> = match &args.format {
+        Some(template) => template.columns().collect(),
+        None => args.columns.clone(),
+    };
+
+    let client = match context::resolve(args.context.as_deref()) {
+        Ok(endpoint) => DockerClient::new(&endpoint),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let action = match args.command.clone() {
+        None => None,
+        Some(Command::Tui) => {
+            tui::run(&client, &args).await;
+            return;
+        }
+        Some(Command::Images {
+            all,
+            dangling,
+            no_truncate,
+            output,
+        }) => {
+            images::run(
+                &client,
+                all,
+                dangling,
+                !no_truncate,
+                output,
+                &args.theme,
+                &args.style,
+            )
+            .await;
+            return;
+        }
+        Some(Command::Inspect {
+            container,
+            show_secrets,
+        }) => {
+            if !inspect::run(&client, &container, show_secrets, &args.style).await {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Logs {
+            containers,
+            filters,
+            follow,
+            since,
+            tail,
+            timestamps,
+            no_prefix,
+        }) => {
+            let options = logs::LogOptions {
+                follow,
+                since,
+                tail,
+                timestamps,
+                prefix: !no_prefix,
+            };
+            if !logs::run(&client, &containers, &filters, options, &args.theme).await {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Networks {
+            no_truncate,
+            output,
+        }) => {
+            networks::run(&client, !no_truncate, output, &args.style).await;
+            return;
+        }
+        Some(Command::Stats { no_stream }) => {
+            stats::run(&client, no_stream, &args.style).await;
+            return;
+        }
+        Some(Command::Volumes {
+            orphaned,
+            no_truncate,
+            output,
+        }) => {
+            volumes::run(
+                &client,
+                orphaned,
+                !no_truncate,
+                output,
+                &args.theme,
+                &args.style,
+            )
+            .await;
+            return;
+        }
+        Some(Command::Start { containers }) => Some((Action::Start, containers, false)),
+        Some(Command::Stop { containers, time }) => {
+            Some((Action::Stop { time }, containers, false))
+        }
+        Some(Command::Restart { containers, time }) => {
+            Some((Action::Restart { time }, containers, false))
+        }
+        Some(Command::Kill {
+            containers,
+            signal,
+            yes,
+        }) => Some((Action::Kill { signal }, containers, yes)),
+        Some(Command::Rm {
+            containers,
+            force,
+            volumes,
+            yes,
+        }) => Some((Action::Remove { force, volumes }, containers, yes)),
+    };
+    if let Some((action, containers, yes)) = action {
+        if !actions::run(&client, &action, &containers, yes).await {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(interval) = args.watch {
+        print!("\x1b[2J");
+        watch::watch(&client, &args, &columns, interval).await;
+        return;
+    }
+
+    let output = match load_containers(&client, &args, &columns).await {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if args.raw {
+        output::print_raw(&output, args.output);
+        return;
+    }
+
+    if let Some(by) = &args.group_by {
+        group::print_grouped(&args, &columns, output, by);
+        return;
+    }
+
+    let containers = convert_containers(&output, !args.no_truncate);
+
+    if args.output.is_structured() {
+        output::print_raw(&containers, args.output);
+        return;
+    }
+
+    if args.output.is_report() {
+        let report = output::Report {
+            title: "Containers".to_string(),
+            host: host_name(&client).await,
+            headers: columns.iter().map(|c| c.header()).collect(),
+            records: containers
+                .iter()
+                .map(|d| columns.iter().map(|c| c.value(d)).collect())
+                .collect(),
+            classes: containers
+                .iter()
+                .map(|d| match d.health {
+                    Health::None => format!("state-{}", d.state),
+                    health => format!("state-{} health-{}", d.state, health.as_str()),
+                })
+                .collect(),
+        };
+        output::print_report(&report, args.output, args.style.stylesheet);
+        return;
+    }
+
+    if args.output != OutputFormat::Table {
+        output::print_records(
+            columns.iter().map(|c| c.header()).collect(),
+            containers
+                .iter()
+                .map(|d| columns.iter().map(|c| c.value(d)).collect()),
+            args.output,
+            args.style.header,
+        );
+        return;
+    }
+
+    println!("{}", render(&args, &columns, &containers, None));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_filter_splits_on_the_first_equals_sign() {
+        assert_eq!(
+            parse_filter("status=running"),
+            Ok(("status".to_string(), "running".to_string()))
+        );
+        assert_eq!(
+            parse_filter("label=com.example=a=b"),
+            Ok(("label".to_string(), "com.example=a=b".to_string()))
+        );
+        assert_eq!(
+            parse_filter("name="),
+            Ok(("name".to_string(), String::new()))
+        );
+    }
+
+    #[test]
+    fn parse_filter_rejects_unknown_keys_and_missing_values() {
+        for filter in ["", "status", "=running", "colour=red", "Status=running"] {
+            assert!(parse_filter(filter).is_err(), "{:?} was accepted", filter);
+        }
+    }
+
+    #[test]
+    fn health_serializes_like_it_is_shown() {
+        for health in [
+            Health::None,
+            Health::Starting,
+            Health::Healthy,
+            Health::Unhealthy,
+        ] {
+            assert_eq!(
+                serde_json::to_string(&health).unwrap(),
+                format!("\"{}\"", health.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn parse_since_accepts_timestamps_and_dates() {
+        assert_eq!(parse_since("1700000000"), Ok(1_700_000_000));
+        assert_eq!(parse_since("2024-01-01T00:00:00Z"), Ok(1_704_067_200));
+        assert_eq!(parse_since("2024-01-01T02:00:00+02:00"), Ok(1_704_067_200));
+    }
+
+    #[test]
+    fn parse_since_accepts_durations() {
+        for (since, seconds) in [
+            ("90s", 90),
+            ("42m", 42 * 60),
+            ("3h", 3 * 3600),
+            ("2d", 2 * 86400),
+        ] {
+            let expected = chrono::Utc::now().timestamp() - seconds;
+            let got = parse_since(since).unwrap();
+            assert!((expected - got).abs() <= 1, "{} gave {}", since, got);
+        }
+    }
+
+    #[test]
+    fn parse_since_rejects_garbage() {
+        for since in ["", "m", "s", "-5m", "5w", "abc", "5 m", "é", "1.5h"] {
+            assert!(parse_since(since).is_err(), "{:?} was accepted", since);
+        }
+    }
+
+    #[test]
+    fn parse_since_rejects_overflowing_durations() {
+        assert!(parse_since("9223372036854775807d").is_err());
+        assert!(parse_since("9223372036854775807m").is_err());
+        assert!(parse_since("99999999999999999999s").is_err());
+    }
 }
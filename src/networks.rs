@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+use crate::{
+    client::DockerClient,
+    fetch_containers, host_name,
+    layout::{self, Fit, TableStyle},
+    output::{self, OutputFormat},
+    short_id,
+};
+
+#[derive(Tabled, Serialize, Debug)]
+struct Network {
+    name: String,
+    id: String,
+    driver: String,
+    scope: String,
+    subnets: String,
+    gateways: String,
+    containers: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct NetworkOutput {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Driver")]
+    driver: String,
+    #[serde(rename = "Scope")]
+    scope: String,
+    #[serde(rename = "IPAM", default)]
+    ipam: Option<Ipam>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Ipam {
+    #[serde(rename = "Config", default)]
+    config: Option<Vec<IpamConfig>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct IpamConfig {
+    #[serde(rename = "Subnet", default)]
+    subnet: Option<String>,
+    #[serde(rename = "Gateway", default)]
+    gateway: Option<String>,
+}
+
+pub async fn run(client: &DockerClient, truncate: bool, format: OutputFormat, style: &TableStyle) {
+    let mut output = client
+        .get("/networks")
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json::<Vec<NetworkOutput>>()
+        .await
+        .expect("Failed to parse JSON response (are you sure the Docker daemon is running?)");
+    output.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // The list endpoint leaves `Containers` empty, and inspecting every network is one
+    // request each, so take the attachments from the container list instead.
+    let mut attached: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for d in fetch_containers(client, true, &[], false).await {
+        let name = d.names.first().cloned().unwrap_or_default();
+        for endpoint in d.network_settings.iter().flat_map(|n| n.networks.values()) {
+            let entry = if endpoint.ip_address.is_empty() {
+                name.clone()
+            } else {
+                format!("{} ({})", name, endpoint.ip_address)
+            };
+            attached
+                .entry(endpoint.network_id.clone())
+                .or_default()
+                .push(entry);
+        }
+    }
+
+    let separator = if format == OutputFormat::Table {
+        "\n"
+    } else {
+        ", "
+    };
+    let networks: Vec<Network> = output
+        .into_iter()
+        .map(|n| {
+            let config = n.ipam.and_then(|ipam| ipam.config).unwrap_or_default();
+            Network {
+                subnets: config
+                    .iter()
+                    .filter_map(|c| c.subnet.clone())
+                    .collect::<Vec<_>>()
+                    .join(separator),
+                gateways: config
+                    .iter()
+                    .filter_map(|c| c.gateway.clone())
+                    .collect::<Vec<_>>()
+                    .join(separator),
+                containers: attached.remove(&n.id).unwrap_or_default().join(separator),
+                id: if truncate { short_id(&n.id) } else { n.id },
+                name: n.name,
+                driver: n.driver,
+                scope: n.scope,
+            }
+        })
+        .collect();
+
+    if format.is_structured() {
+        output::print_raw(&networks, format);
+        return;
+    }
+
+    if format.is_report() {
+        let report = output::Report {
+            title: "Networks".to_string(),
+            host: host_name(client).await,
+            headers: Network::headers().into_iter().map(String::from).collect(),
+            records: networks
+                .iter()
+                .map(|n| n.fields().into_iter().map(String::from).collect())
+                .collect(),
+            classes: Vec::new(),
+        };
+        output::print_report(&report, format, style.stylesheet);
+        return;
+    }
+
+    if format != OutputFormat::Table {
+        output::print_records(
+            Network::headers().into_iter().map(String::from).collect(),
+            networks
+                .iter()
+                .map(|n| n.fields().into_iter().map(String::from).collect()),
+            format,
+            style.header,
+        );
+        return;
+    }
+
+    let fits = [
+        Fit::Ellipsize(1),
+        Fit::Fixed,
+        Fit::Fixed,
+        Fit::Fixed,
+        Fit::Wrap(1),
+        Fit::Wrap(1),
+        Fit::Wrap(2),
+    ];
+    println!("{}", layout::table(&networks, &fits, style, truncate));
+}
@@ -0,0 +1,217 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+    /// A report to paste into docs and wikis
+    Markdown,
+    /// A self-contained HTML report
+    Html,
+}
+
+impl OutputFormat {
+    pub fn is_structured(self) -> bool {
+        matches!(self, Self::Json | Self::Ndjson)
+    }
+
+    pub fn is_report(self) -> bool {
+        matches!(self, Self::Markdown | Self::Html)
+    }
+}
+
+/// A titled table for the markdown and HTML reports, with when and where it was
+/// taken.
+pub struct Report {
+    pub title: String,
+    /// The name of the docker host the records came from.
+    pub host: String,
+    pub headers: Vec<String>,
+    pub records: Vec<Vec<String>>,
+    /// CSS classes for each record's row, e.g. `state-exited`. May be left empty.
+    pub classes: Vec<String>,
+}
+
+/// Colours match the default terminal theme.
+const STYLESHEET: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; margin-bottom: 0.2em; }
+p.generated { color: #666; margin-top: 0; }
+table { border-collapse: collapse; font-size: 0.9em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f3f3f3; }
+td { font-family: ui-monospace, monospace; white-space: pre-wrap; }
+tr.state-paused, tr.state-restarting, tr.state-created { color: #9a6700; }
+tr.state-exited, tr.state-dead, tr.state-removing { color: #c62828; }
+tr.orphaned { color: #9a6700; }
+tr.health-healthy td.health { color: #2e7d32; }
+tr.health-starting td.health { color: #9a6700; }
+tr.health-unhealthy td.health { color: #c62828; font-weight: bold; }
+";
+
+/// Prints a report as markdown or as an HTML page, with the stylesheet embedded
+/// unless `stylesheet` is off.
+pub fn print_report(report: &Report, format: OutputFormat, stylesheet: bool) {
+    let now = chrono::Local::now();
+    let generated = now.format("%Y-%m-%d %H:%M:%S %z").to_string();
+    match format {
+        OutputFormat::Markdown => {
+            println!("# {}\n", escape_markdown(&report.title));
+            println!(
+                "Generated at {} on `{}`.\n",
+                generated,
+                report.host.replace('`', "'")
+            );
+            let row = |fields: &[String]| {
+                let cells: Vec<String> = fields.iter().map(|f| escape_markdown(f)).collect();
+                format!("| {} |", cells.join(" | "))
+            };
+            println!("{}", row(&report.headers));
+            println!("|{}", " --- |".repeat(report.headers.len()));
+            for record in &report.records {
+                println!("{}", row(record));
+            }
+        }
+        OutputFormat::Html => {
+            println!("<!DOCTYPE html>");
+            println!("<html lang=\"en\">");
+            println!("<head>");
+            println!("<meta charset=\"utf-8\">");
+            println!(
+                "<title>{} on {}</title>",
+                escape_html(&report.title),
+                escape_html(&report.host)
+            );
+            if stylesheet {
+                println!("<style>\n{}</style>", STYLESHEET);
+            }
+            println!("</head>");
+            println!("<body>");
+            println!("<h1>{}</h1>", escape_html(&report.title));
+            println!(
+                "<p class=\"generated\">Generated at <time datetime=\"{}\">{}</time> on <code>{}</code>.</p>",
+                now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+                generated,
+                escape_html(&report.host)
+            );
+            println!("<table>");
+            println!("<thead>");
+            println!(
+                "<tr>{}</tr>",
+                report
+                    .headers
+                    .iter()
+                    .map(|h| format!("<th>{}</th>", escape_html(h)))
+                    .collect::<String>()
+            );
+            println!("</thead>");
+            println!("<tbody>");
+            let columns: Vec<String> = report.headers.iter().map(|h| class_name(h)).collect();
+            for (i, record) in report.records.iter().enumerate() {
+                let cells: String = record
+                    .iter()
+                    .zip(&columns)
+                    .map(|(field, column)| {
+                        format!("<td class=\"{}\">{}</td>", column, escape_html(field))
+                    })
+                    .collect();
+                match report.classes.get(i).filter(|c| !c.is_empty()) {
+                    Some(class) => println!("<tr class=\"{}\">{}</tr>", escape_html(class), cells),
+                    None => println!("<tr>{}</tr>", cells),
+                }
+            }
+            println!("</tbody>");
+            println!("</table>");
+            println!("</body>");
+            println!("</html>");
+        }
+        _ => unreachable!("{:?} is not a report format", format),
+    }
+}
+
+/// Prints already formatted records as CSV or TSV, header line first unless `header`
+/// is off.
+pub fn print_records<I>(headers: Vec<String>, records: I, format: OutputFormat, header: bool)
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    let (delimiter, escape): (&str, fn(&str) -> String) = match format {
+        OutputFormat::Csv => (",", escape_csv),
+        OutputFormat::Tsv => ("\t", escape_tsv),
+        _ => unreachable!("{:?} is not a delimited format", format),
+    };
+    let join = |fields: Vec<String>| {
+        fields
+            .iter()
+            .map(|f| escape(f))
+            .collect::<Vec<_>>()
+            .join(delimiter)
+    };
+
+    if header {
+        println!("{}", join(headers));
+    }
+    for record in records {
+        println!("{}", join(record));
+    }
+}
+
+/// Prints anything serializable as JSON or newline-delimited JSON.
+pub fn print_raw<T: Serialize>(rows: &[T], format: OutputFormat) {
+    match format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(rows).expect("Failed to serialize output")
+        ),
+        OutputFormat::Ndjson => {
+            for row in rows {
+                println!(
+                    "{}",
+                    serde_json::to_string(row).expect("Failed to serialize output")
+                );
+            }
+        }
+        _ => unreachable!("{:?} is not a structured format", format),
+    }
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn escape_tsv(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+fn escape_markdown(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\n', '\r'], " ")
+}
+
+fn escape_html(field: &str) -> String {
+    field
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Turns a column header such as `cpu %` into a class name (`cpu`).
+fn class_name(header: &str) -> String {
+    header
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -0,0 +1,391 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+use tokio::{
+    sync::mpsc,
+    task::JoinSet,
+    time::{Instant, MissedTickBehavior},
+};
+
+use crate::{
+    STATS_TIMEOUT,
+    client::{DockerClient, JsonLines},
+    fetch_containers, human_size,
+    layout::{self, Fit, TableStyle},
+    try_fetch_containers,
+    watch::{RESYNC_INTERVAL, next_event, subscribe_events, write_frame},
+};
+
+/// The daemon produces a new sample about once a second.
+const REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Stats {
+    cpu_stats: CpuStats,
+    precpu_stats: CpuStats,
+    memory_stats: MemoryStats,
+    networks: Option<HashMap<String, NetworkStats>>,
+    blkio_stats: BlkioStats,
+    pids_stats: PidsStats,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct CpuStats {
+    cpu_usage: CpuUsage,
+    system_cpu_usage: Option<u64>,
+    online_cpus: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct CpuUsage {
+    total_usage: u64,
+    percpu_usage: Option<Vec<u64>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct MemoryStats {
+    usage: u64,
+    limit: u64,
+    stats: Option<HashMap<String, u64>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct NetworkStats {
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct BlkioStats {
+    io_service_bytes_recursive: Option<Vec<BlkioEntry>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct BlkioEntry {
+    op: String,
+    value: u64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct PidsStats {
+    current: u64,
+}
+
+/// One stats sample boiled down to what `docker stats` shows.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub cpu_percent: f64,
+    pub memory: u64,
+    pub memory_limit: u64,
+    pub net_rx: u64,
+    pub net_tx: u64,
+    pub block_read: u64,
+    pub block_write: u64,
+    pub pids: u64,
+}
+
+impl Usage {
+    pub fn from_stats(stats: &Stats) -> Self {
+        // Same calculation as the docker CLI: the container's share of the host's CPU time
+        // since the previous sample, scaled up to the number of CPUs.
+        let cpu = &stats.cpu_stats;
+        let precpu = &stats.precpu_stats;
+        let cpu_delta = cpu
+            .cpu_usage
+            .total_usage
+            .saturating_sub(precpu.cpu_usage.total_usage);
+        let system_delta = cpu
+            .system_cpu_usage
+            .unwrap_or_default()
+            .saturating_sub(precpu.system_cpu_usage.unwrap_or_default());
+        let cpus = cpu
+            .online_cpus
+            .or_else(|| cpu.cpu_usage.percpu_usage.as_ref().map(|p| p.len() as u64))
+            .unwrap_or(1);
+        let cpu_percent = if system_delta > 0 {
+            cpu_delta as f64 / system_delta as f64 * cpus as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        // The page cache counts towards `usage` but can be reclaimed at any time, so the
+        // CLI leaves it out (`total_inactive_file` on cgroup v1, `inactive_file` on v2).
+        let memory_stats = &stats.memory_stats;
+        let cache = memory_stats
+            .stats
+            .as_ref()
+            .and_then(|s| s.get("total_inactive_file").or(s.get("inactive_file")))
+            .copied()
+            .unwrap_or_default();
+
+        let networks = stats.networks.iter().flat_map(|n| n.values());
+        let blkio = stats
+            .blkio_stats
+            .io_service_bytes_recursive
+            .iter()
+            .flatten();
+        let block = |op: &str| {
+            blkio
+                .clone()
+                .filter(|e| e.op.eq_ignore_ascii_case(op))
+                .map(|e| e.value)
+                .sum()
+        };
+
+        Usage {
+            cpu_percent,
+            memory: memory_stats.usage.saturating_sub(cache),
+            memory_limit: memory_stats.limit,
+            net_rx: networks.clone().map(|n| n.rx_bytes).sum(),
+            net_tx: networks.map(|n| n.tx_bytes).sum(),
+            block_read: block("read"),
+            block_write: block("write"),
+            pids: stats.pids_stats.current,
+        }
+    }
+
+    pub fn memory_percent(&self) -> f64 {
+        if self.memory_limit == 0 {
+            0.0
+        } else {
+            self.memory as f64 / self.memory_limit as f64 * 100.0
+        }
+    }
+}
+
+#[derive(Tabled, Debug)]
+struct StatsRow {
+    id: String,
+    name: String,
+    #[tabled(rename = "cpu %")]
+    cpu: String,
+    #[tabled(rename = "mem usage / limit")]
+    memory: String,
+    #[tabled(rename = "mem %")]
+    memory_percent: String,
+    #[tabled(rename = "net i/o")]
+    net: String,
+    #[tabled(rename = "block i/o")]
+    block: String,
+    pids: String,
+}
+
+impl StatsRow {
+    fn new(id: &str, name: &str, usage: Option<&Usage>) -> Self {
+        let id = id[..id.len().min(12)].to_string();
+        let name = name.trim_start_matches('/').to_string();
+        let Some(u) = usage else {
+            let missing = || "--".to_string();
+            return StatsRow {
+                id,
+                name,
+                cpu: missing(),
+                memory: missing(),
+                memory_percent: missing(),
+                net: missing(),
+                block: missing(),
+                pids: missing(),
+            };
+        };
+
+        let size = |bytes: u64| human_size(bytes as i64);
+        StatsRow {
+            id,
+            name,
+            cpu: format!("{:.2}%", u.cpu_percent),
+            memory: format!("{} / {}", size(u.memory), size(u.memory_limit)),
+            memory_percent: format!("{:.2}%", u.memory_percent()),
+            net: format!("{} / {}", size(u.net_rx), size(u.net_tx)),
+            block: format!("{} / {}", size(u.block_read), size(u.block_write)),
+            pids: u.pids.to_string(),
+        }
+    }
+}
+
+struct Tracked {
+    name: String,
+    usage: Option<Usage>,
+}
+
+fn render(tracked: &BTreeMap<String, Tracked>, style: &TableStyle) -> String {
+    let mut rows: Vec<StatsRow> = tracked
+        .iter()
+        .map(|(id, t)| StatsRow::new(id, &t.name, t.usage.as_ref()))
+        .collect();
+    rows.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut fits = [Fit::Fixed; 8];
+    fits[1] = Fit::Ellipsize(1);
+    layout::table(&rows, &fits, style, true).to_string()
+}
+
+/// Fetches a single sample. Without `one-shot` the daemon waits for a second sample so
+/// the CPU percentage can be calculated.
+pub async fn fetch_usage(client: &DockerClient, id: &str) -> Option<Usage> {
+    let stats = client
+        .get(&format!("/containers/{}/stats", id))
+        .query(&[("stream", "false")])
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json::<Stats>()
+        .await
+        .ok()?;
+    Some(Usage::from_stats(&stats))
+}
+
+/// Follows the stats stream of one container in the background. Sends `None` once the
+/// stream ends, which happens when the container stops.
+fn stream_usage(client: &DockerClient, id: String, tx: mpsc::Sender<(String, Option<Usage>)>) {
+    let request = client
+        .get(&format!("/containers/{}/stats", id))
+        .query(&[("stream", "true")]);
+
+    tokio::spawn(async move {
+        if let Ok(response) = request.send().await
+            && response.status().is_success()
+        {
+            let mut lines = JsonLines::new(response);
+            while let Some(stats) = lines.next::<Stats>().await {
+                if tx
+                    .send((id.clone(), Some(Usage::from_stats(&stats))))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+        let _ = tx.send((id, None)).await;
+    });
+}
+
+/// Starts streams for running containers that aren't tracked yet.
+async fn track_new(
+    client: &DockerClient,
+    tracked: &mut BTreeMap<String, Tracked>,
+    tx: &mpsc::Sender<(String, Option<Usage>)>,
+) -> Result<(), String> {
+    for d in try_fetch_containers(client, false, &[], false).await? {
+        if tracked.contains_key(&d.id) {
+            continue;
+        }
+        stream_usage(client, d.id.clone(), tx.clone());
+        tracked.insert(
+            d.id,
+            Tracked {
+                name: d.names.first().cloned().unwrap_or_default(),
+                usage: None,
+            },
+        );
+    }
+    Ok(())
+}
+
+pub async fn run(client: &DockerClient, no_stream: bool, style: &TableStyle) {
+    if no_stream {
+        let mut tracked = BTreeMap::new();
+        let mut tasks = JoinSet::new();
+        for d in fetch_containers(client, false, &[], false).await {
+            let client = client.clone();
+            let id = d.id.clone();
+            tasks.spawn(async move {
+                // A hung container is shown as `--` rather than holding up the table.
+                let usage = tokio::time::timeout(STATS_TIMEOUT, fetch_usage(&client, &id))
+                    .await
+                    .ok()
+                    .flatten();
+                (id, usage)
+            });
+            tracked.insert(
+                d.id,
+                Tracked {
+                    name: d.names.first().cloned().unwrap_or_default(),
+                    usage: None,
+                },
+            );
+        }
+        while let Some(res) = tasks.join_next().await {
+            let (id, usage) = res.expect("Stats task panicked");
+            if let Some(t) = tracked.get_mut(&id) {
+                t.usage = usage;
+            }
+        }
+
+        println!("{}", render(&tracked, style));
+        return;
+    }
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let mut tracked = BTreeMap::new();
+    // Containers are tracked again on the next tick while the daemon can't be reached,
+    // and the event stream is picked up again once it answers.
+    let mut error = track_new(client, &mut tracked, &tx).await.err();
+
+    let mut events = Some(subscribe_events(client));
+    let mut ticker = tokio::time::interval(REDRAW_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut resync = tokio::time::interval_at(Instant::now() + RESYNC_INTERVAL, RESYNC_INTERVAL);
+    resync.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    print!("\x1b[2J");
+    loop {
+        let retrack = tokio::select! {
+            _ = ticker.tick() => {
+                let heading = match &error {
+                    Some(e) => format!("fancy-docker stats (error: {})", e),
+                    None => "fancy-docker stats".to_string(),
+                };
+                write_frame(&heading, &render(&tracked, style));
+                error.is_some()
+            }
+            _ = resync.tick() => true,
+            event = next_event(&mut events) => match event {
+                Some(_) => true,
+                None => {
+                    events = None;
+                    true
+                }
+            },
+            Some((id, usage)) = rx.recv() => {
+                match usage {
+                    Some(usage) => {
+                        if let Some(t) = tracked.get_mut(&id) {
+                            t.usage = Some(usage);
+                        }
+                    }
+                    None => {
+                        tracked.remove(&id);
+                    }
+                }
+                false
+            }
+        };
+
+        if retrack {
+            match track_new(client, &mut tracked, &tx).await {
+                Ok(()) => {
+                    if events.is_none() && error.is_some() {
+                        events = Some(subscribe_events(client));
+                    }
+                    error = None;
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{Column, Docker};
+
+/// A tiny subset of Go's text/template, enough for `docker ps --format` style strings.
+#[derive(Debug, Clone)]
+pub struct Template {
+    table: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Field(Function, Option<Column>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Print,
+    Json,
+    Upper,
+    Lower,
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let (table, body) = match input.strip_prefix("table") {
+            Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => {
+                (true, rest.trim_start())
+            }
+            _ => (false, input),
+        };
+        let body = body.replace("\\t", "\t").replace("\\n", "\n");
+
+        let mut segments = Vec::new();
+        let mut rest = body.as_str();
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find("}}")
+                .ok_or_else(|| format!("unclosed action in '{}'", input))?;
+            segments.push(parse_action(&rest[start + 2..start + end])?);
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        if table && segments.is_empty() {
+            return Err("table format needs at least one field".to_string());
+        }
+
+        Ok(Template { table, segments })
+    }
+
+    /// Every column the template refers to, so the caller knows what to fetch.
+    pub fn columns(&self) -> impl Iterator<Item = Column> + '_ {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Field(_, column) => *column,
+            Segment::Literal(_) => None,
+        })
+    }
+
+    pub fn is_table(&self) -> bool {
+        self.table
+    }
+
+    pub fn render(&self, row: &Docker) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Field(function, column) => out.push_str(&apply(*function, *column, row)),
+            }
+        }
+        out
+    }
+
+    /// Header cells for table templates, split on tabs like the rendered rows.
+    pub fn headers(&self) -> Vec<String> {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Field(_, Some(column)) => out.push_str(header_for(*column)),
+                Segment::Field(_, None) => out.push_str("CONTAINER"),
+            }
+        }
+        split_cells(&out)
+    }
+
+    pub fn cells(&self, row: &Docker) -> Vec<String> {
+        split_cells(&self.render(row))
+    }
+}
+
+fn split_cells(line: &str) -> Vec<String> {
+    line.split('\t')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+fn parse_action(action: &str) -> Result<Segment, String> {
+    let words: Vec<&str> = action.split_whitespace().collect();
+    let (function, path) = match words.as_slice() {
+        [path] => (Function::Print, *path),
+        [name, path] => {
+            let function = match *name {
+                "json" => Function::Json,
+                "upper" => Function::Upper,
+                "lower" => Function::Lower,
+                _ => return Err(format!("unknown function '{}'", name)),
+            };
+            (function, *path)
+        }
+        _ => return Err(format!("unsupported action '{{{{{}}}}}'", action)),
+    };
+
+    // The whole row is printed as JSON, either way it's asked for.
+    if path == "." {
+        return match function {
+            Function::Print | Function::Json => Ok(Segment::Field(Function::Json, None)),
+            _ => Err(format!("'{}' needs a field like .Names, not '.'", words[0])),
+        };
+    }
+
+    let name = path
+        .strip_prefix('.')
+        .ok_or_else(|| format!("expected a field like .Names, got '{}'", path))?;
+    let column = column_for(name).ok_or_else(|| {
+        format!(
+            "unknown field '.{}' (valid fields: {})",
+            name,
+            FIELDS
+                .iter()
+                .map(|(n, _, _)| *n)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })?;
+
+    Ok(Segment::Field(function, Some(column)))
+}
+
+/// Field name, column and table header, named after the docker CLI equivalents.
+const FIELDS: &[(&str, Column, &str)] = &[
+    ("ID", Column::Id, "CONTAINER ID"),
+    ("Image", Column::Image, "IMAGE"),
+    ("Names", Column::Name, "NAMES"),
+    ("Command", Column::Command, "COMMAND"),
+    ("RunningFor", Column::Created, "CREATED"),
+    ("CreatedAt", Column::CreatedAt, "CREATED AT"),
+    ("Status", Column::Status, "STATUS"),
+    ("Ports", Column::Ports, "PORTS"),
+    ("State", Column::State, "STATE"),
+    ("Health", Column::Health, "HEALTH"),
+    ("LastCheck", Column::LastCheck, "LAST CHECK"),
+    ("Labels", Column::Labels, "LABELS"),
+    ("Mounts", Column::Mounts, "MOUNTS"),
+    ("Networks", Column::Networks, "NETWORKS"),
+    ("Size", Column::Size, "SIZE"),
+    ("RestartCount", Column::Restarts, "RESTARTS"),
+    ("CPUPerc", Column::Cpu, "CPU %"),
+    ("MemUsage", Column::Memory, "MEM USAGE / LIMIT"),
+    ("Project", Column::Project, "PROJECT"),
+    ("Service", Column::Service, "SERVICE"),
+];
+
+fn column_for(name: &str) -> Option<Column> {
+    FIELDS
+        .iter()
+        .find(|(field, _, _)| field.eq_ignore_ascii_case(name))
+        .map(|(_, column, _)| *column)
+}
+
+fn header_for(column: Column) -> &'static str {
+    FIELDS
+        .iter()
+        .find(|(_, c, _)| *c == column)
+        .map(|(_, _, header)| *header)
+        .unwrap_or_default()
+}
+
+fn apply(function: Function, column: Option<Column>, row: &Docker) -> String {
+    let Some(column) = column else {
+        return serde_json::to_string(row).expect("Failed to serialize output");
+    };
+
+    let value = column.value(row);
+    match function {
+        Function::Print => value,
+        Function::Json => serde_json::to_string(&value).expect("Failed to serialize output"),
+        Function::Upper => value.to_uppercase(),
+        Function::Lower => value.to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> Docker {
+        Docker {
+            id: "a1b2c3d4e5f6".to_string(),
+            image: "nginx:latest".to_string(),
+            name: "web".to_string(),
+            status: "Up 1 hour".to_string(),
+            ..Docker::default()
+        }
+    }
+
+    #[test]
+    fn renders_fields_and_functions() {
+        let template =
+            Template::parse("{{.ID}} {{upper .Names}} {{lower .Image}} {{json .Status}}").unwrap();
+        assert!(!template.is_table());
+        assert_eq!(
+            template.render(&row()),
+            r#"a1b2c3d4e5f6 WEB nginx:latest "Up 1 hour""#
+        );
+        assert_eq!(
+            template.columns().collect::<Vec<_>>(),
+            [Column::Id, Column::Name, Column::Image, Column::Status]
+        );
+    }
+
+    #[test]
+    fn field_names_ignore_case() {
+        let template = Template::parse("{{.names}}").unwrap();
+        assert_eq!(template.render(&row()), "web");
+    }
+
+    #[test]
+    fn table_templates_have_headers_and_cells() {
+        let template = Template::parse("table {{.ID}}\\t{{.Names}}").unwrap();
+        assert!(template.is_table());
+        assert_eq!(template.headers(), ["CONTAINER ID", "NAMES"]);
+        assert_eq!(template.cells(&row()), ["a1b2c3d4e5f6", "web"]);
+    }
+
+    #[test]
+    fn only_a_leading_table_word_makes_a_table() {
+        let template = Template::parse("tables {{.ID}}").unwrap();
+        assert!(!template.is_table());
+        assert_eq!(template.render(&row()), "tables a1b2c3d4e5f6");
+    }
+
+    #[test]
+    fn the_whole_row_is_printed_as_json() {
+        let json = serde_json::to_string(&row()).unwrap();
+        assert_eq!(Template::parse("{{.}}").unwrap().render(&row()), json);
+        assert_eq!(Template::parse("{{json .}}").unwrap().render(&row()), json);
+        assert!(Template::parse("{{upper .}}").is_err());
+    }
+
+    #[test]
+    fn literals_and_escapes_are_kept() {
+        let template = Template::parse("name: {{.Names}}\\n").unwrap();
+        assert_eq!(template.render(&row()), "name: web\n");
+        assert_eq!(Template::parse("").unwrap().render(&row()), "");
+        assert_eq!(Template::parse("plain").unwrap().render(&row()), "plain");
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        for input in [
+            "{{.ID",
+            "{{}}",
+            "{{ }}",
+            "{{ID}}",
+            "{{.Nope}}",
+            "{{shout .ID}}",
+            "{{json .ID .Names}}",
+            "table",
+            "table ",
+        ] {
+            assert!(Template::parse(input).is_err(), "{:?} was accepted", input);
+        }
+    }
+}
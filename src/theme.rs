@@ -0,0 +1,270 @@
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+use tabled::settings::Color;
+
+use crate::Health;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colour when writing to a terminal and `NO_COLOR` isn't set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// `NO_COLOR` (https://no-color.org) only turns off the automatic choice, an
+    /// explicit `--color always` still wins.
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::io::stdout().is_terminal()
+                    && !dotenvy::var("NO_COLOR").is_ok_and(|v| !v.is_empty())
+            }
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeName {
+    Default,
+    /// Brighter colours for dark terminals
+    Bright,
+    /// Bold and underline only
+    Mono,
+}
+
+/// Colours for everything that gets highlighted. The default value colours nothing,
+/// which is what's used when colour is turned off.
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    pub running: Option<Color>,
+    /// Paused, restarting or created.
+    pub pending: Option<Color>,
+    /// Exited, dead or being removed.
+    pub stopped: Option<Color>,
+    pub unknown: Option<Color>,
+    pub healthy: Option<Color>,
+    pub starting: Option<Color>,
+    pub unhealthy: Option<Color>,
+    /// Ports published on all interfaces (0.0.0.0 or ::).
+    pub public_port: Option<Color>,
+    pub image_tag: Option<Color>,
+    pub added: Option<Color>,
+    pub changed: Option<Color>,
+    pub removed: Option<Color>,
+    pub orphaned: Option<Color>,
+    pub heading: Option<Color>,
+    /// Handed out to containers in order when interleaving logs.
+    pub prefixes: Vec<Color>,
+}
+
+const KEYS: &[&str] = &[
+    "running",
+    "pending",
+    "stopped",
+    "unknown",
+    "healthy",
+    "starting",
+    "unhealthy",
+    "public-port",
+    "image-tag",
+    "added",
+    "changed",
+    "removed",
+    "orphaned",
+    "heading",
+];
+
+impl Theme {
+    /// Picks the theme from `--theme` or `FANCY_DOCKER_THEME`, then applies overrides
+    /// from `FANCY_DOCKER_COLORS` (e.g. `stopped=magenta,public-port=bold+red`). Both
+    /// can also be set in a `.env` file.
+    /// Settings are checked even when colour ends up turned off, so mistakes don't go
+    /// unnoticed until the output is a terminal.
+    pub fn load(choice: ColorChoice, name: Option<ThemeName>) -> Result<Self, String> {
+        let name = match name {
+            Some(name) => name,
+            None => match dotenvy::var("FANCY_DOCKER_THEME") {
+                Ok(name) => ThemeName::from_str(&name, true).map_err(|_| {
+                    format!(
+                        "unknown theme '{}' in FANCY_DOCKER_THEME (expected default, bright or mono)",
+                        name
+                    )
+                })?,
+                Err(_) => ThemeName::Default,
+            },
+        };
+
+        let mut theme = Theme::builtin(name);
+        if let Ok(overrides) = dotenvy::var("FANCY_DOCKER_COLORS") {
+            for entry in overrides.split(',').filter(|e| !e.trim().is_empty()) {
+                let (key, spec) = entry.split_once('=').ok_or_else(|| {
+                    format!("expected KEY=COLOR in FANCY_DOCKER_COLORS, got '{}'", entry)
+                })?;
+                theme.set(key.trim(), parse_color(spec.trim())?)?;
+            }
+        }
+
+        if !choice.enabled() {
+            return Ok(Theme::default());
+        }
+        Ok(theme)
+    }
+
+    fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Default => Theme {
+                running: None,
+                pending: Some(Color::FG_YELLOW),
+                stopped: Some(Color::FG_RED),
+                unknown: Some(Color::FG_BRIGHT_BLACK),
+                healthy: Some(Color::FG_GREEN),
+                starting: Some(Color::FG_YELLOW),
+                unhealthy: Some(Color::BOLD | Color::FG_RED),
+                public_port: Some(Color::FG_MAGENTA),
+                image_tag: Some(Color::FG_CYAN),
+                added: Some(Color::BOLD | Color::FG_GREEN),
+                changed: Some(Color::BOLD | Color::FG_CYAN),
+                removed: Some(Color::FG_BRIGHT_BLACK),
+                orphaned: Some(Color::FG_YELLOW),
+                heading: Some(Color::BOLD),
+                prefixes: vec![
+                    Color::FG_CYAN,
+                    Color::FG_YELLOW,
+                    Color::FG_GREEN,
+                    Color::FG_MAGENTA,
+                    Color::FG_BLUE,
+                    Color::FG_RED,
+                ],
+            },
+            ThemeName::Bright => Theme {
+                running: Some(Color::FG_BRIGHT_WHITE),
+                pending: Some(Color::FG_BRIGHT_YELLOW),
+                stopped: Some(Color::FG_BRIGHT_RED),
+                unknown: Some(Color::FG_WHITE),
+                healthy: Some(Color::FG_BRIGHT_GREEN),
+                starting: Some(Color::FG_BRIGHT_YELLOW),
+                unhealthy: Some(Color::BOLD | Color::FG_BRIGHT_RED),
+                public_port: Some(Color::FG_BRIGHT_MAGENTA),
+                image_tag: Some(Color::FG_BRIGHT_CYAN),
+                added: Some(Color::BOLD | Color::FG_BRIGHT_GREEN),
+                changed: Some(Color::BOLD | Color::FG_BRIGHT_CYAN),
+                removed: Some(Color::FG_BRIGHT_BLACK),
+                orphaned: Some(Color::FG_BRIGHT_YELLOW),
+                heading: Some(Color::BOLD | Color::FG_BRIGHT_WHITE),
+                prefixes: vec![
+                    Color::FG_BRIGHT_CYAN,
+                    Color::FG_BRIGHT_YELLOW,
+                    Color::FG_BRIGHT_GREEN,
+                    Color::FG_BRIGHT_MAGENTA,
+                    Color::FG_BRIGHT_BLUE,
+                    Color::FG_BRIGHT_RED,
+                ],
+            },
+            ThemeName::Mono => Theme {
+                stopped: Some(Color::UNDERLINE),
+                unhealthy: Some(Color::BOLD),
+                public_port: Some(Color::UNDERLINE),
+                added: Some(Color::BOLD),
+                changed: Some(Color::BOLD),
+                heading: Some(Color::BOLD),
+                prefixes: vec![Color::BOLD],
+                ..Theme::default()
+            },
+        }
+    }
+
+    fn set(&mut self, key: &str, color: Option<Color>) -> Result<(), String> {
+        let slot = match key {
+            "running" => &mut self.running,
+            "pending" => &mut self.pending,
+            "stopped" => &mut self.stopped,
+            "unknown" => &mut self.unknown,
+            "healthy" => &mut self.healthy,
+            "starting" => &mut self.starting,
+            "unhealthy" => &mut self.unhealthy,
+            "public-port" => &mut self.public_port,
+            "image-tag" => &mut self.image_tag,
+            "added" => &mut self.added,
+            "changed" => &mut self.changed,
+            "removed" => &mut self.removed,
+            "orphaned" => &mut self.orphaned,
+            "heading" => &mut self.heading,
+            _ => {
+                return Err(format!(
+                    "unknown colour key '{}' in FANCY_DOCKER_COLORS (valid keys: {})",
+                    key,
+                    KEYS.join(", ")
+                ));
+            }
+        };
+        *slot = color;
+        Ok(())
+    }
+
+    pub fn state(&self, state: &str) -> Option<Color> {
+        match state {
+            "running" => self.running.clone(),
+            "paused" | "restarting" | "created" => self.pending.clone(),
+            "exited" | "dead" | "removing" => self.stopped.clone(),
+            _ => self.unknown.clone(),
+        }
+    }
+
+    pub fn health(&self, health: Health) -> Option<Color> {
+        match health {
+            Health::None => None,
+            Health::Starting => self.starting.clone(),
+            Health::Healthy => self.healthy.clone(),
+            Health::Unhealthy => self.unhealthy.clone(),
+        }
+    }
+
+    pub fn paint(color: Option<&Color>, text: &str) -> String {
+        match color {
+            Some(color) => color.colorize(text),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Parses `+`-separated colour names and styles, e.g. `bold+bright-red`, or `none`.
+fn parse_color(spec: &str) -> Result<Option<Color>, String> {
+    if spec == "none" {
+        return Ok(None);
+    }
+
+    let mut color: Option<Color> = None;
+    for part in spec.split('+') {
+        let next = match part {
+            "black" => Color::FG_BLACK,
+            "red" => Color::FG_RED,
+            "green" => Color::FG_GREEN,
+            "yellow" => Color::FG_YELLOW,
+            "blue" => Color::FG_BLUE,
+            "magenta" => Color::FG_MAGENTA,
+            "cyan" => Color::FG_CYAN,
+            "white" => Color::FG_WHITE,
+            "bright-black" | "gray" | "grey" => Color::FG_BRIGHT_BLACK,
+            "bright-red" => Color::FG_BRIGHT_RED,
+            "bright-green" => Color::FG_BRIGHT_GREEN,
+            "bright-yellow" => Color::FG_BRIGHT_YELLOW,
+            "bright-blue" => Color::FG_BRIGHT_BLUE,
+            "bright-magenta" => Color::FG_BRIGHT_MAGENTA,
+            "bright-cyan" => Color::FG_BRIGHT_CYAN,
+            "bright-white" => Color::FG_BRIGHT_WHITE,
+            "bold" => Color::BOLD,
+            "underline" => Color::UNDERLINE,
+            _ => return Err(format!("unknown colour '{}' in FANCY_DOCKER_COLORS", part)),
+        };
+        color = Some(match color {
+            Some(color) => color | next,
+            None => next,
+        });
+    }
+    Ok(color)
+}
@@ -0,0 +1,372 @@
+use std::time::Duration;
+
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Cell, Paragraph, Row, Table, TableState, Wrap},
+};
+use tabled::settings::Color as AnsiColor;
+use tokio::{
+    sync::mpsc,
+    time::{Instant, MissedTickBehavior},
+};
+
+use crate::{
+    Args, Column, DockerOutput, Health,
+    actions::{Action, display_name},
+    client::DockerClient,
+    convert_containers, load_containers,
+    theme::Theme,
+    watch::{RESYNC_INTERVAL, next_event, subscribe_events, update_container},
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The ANSI colours in SGR order, normal then bright.
+const COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Turns a theme colour into a ratatui style by reading back the SGR codes that
+/// `FANCY_DOCKER_COLORS` and the built-in themes are made of.
+fn style(color: Option<AnsiColor>) -> Style {
+    let Some(color) = color else {
+        return Style::default();
+    };
+    let codes = color
+        .get_prefix()
+        .split("\u{1b}[")
+        .flat_map(|sgr| sgr.trim_end_matches('m').split(';'))
+        .filter_map(|code| code.parse::<u8>().ok());
+    codes.fold(Style::default(), |style, code| match code {
+        1 => style.add_modifier(Modifier::BOLD),
+        4 => style.add_modifier(Modifier::UNDERLINED),
+        30..=37 => style.fg(COLORS[usize::from(code - 30)]),
+        90..=97 => style.fg(COLORS[usize::from(code - 90) + 8]),
+        _ => style,
+    })
+}
+
+fn state_style(theme: &Theme, state: &str) -> Style {
+    style(theme.state(state))
+}
+
+fn health_style(theme: &Theme, health: Health) -> Style {
+    style(theme.health(health))
+}
+
+/// Reads terminal input on a plain thread, since crossterm's reader is blocking.
+fn spawn_input() -> mpsc::Receiver<Event> {
+    let (tx, rx) = mpsc::channel(16);
+    std::thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if tx.blocking_send(event).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+struct App<'a> {
+    client: &'a DockerClient,
+    args: Args,
+    containers: Vec<DockerOutput>,
+    table: TableState,
+    show_detail: bool,
+    detail_scroll: u16,
+    following: bool,
+    /// A destructive action waiting for the user to press `y`.
+    pending: Option<(Action, DockerOutput)>,
+    message: Option<String>,
+    results: mpsc::Sender<String>,
+}
+
+impl App<'_> {
+    async fn reload(&mut self) -> Result<(), String> {
+        let selected = self.selected().map(|d| d.id.clone());
+        self.containers = load_containers(self.client, &self.args, &self.args.columns).await?;
+        self.reselect(selected);
+        Ok(())
+    }
+
+    /// Reloads while the session is running. If the daemon can't be reached the last
+    /// list stays up and the error goes to the status bar.
+    async fn refresh(&mut self) {
+        if let Err(e) = self.reload().await {
+            self.message = Some(e);
+        }
+    }
+
+    async fn update(&mut self, id: &str) {
+        let selected = self.selected().map(|d| d.id.clone());
+        if let Err(e) = update_container(
+            self.client,
+            &self.args,
+            &self.args.columns,
+            &mut self.containers,
+            id,
+        )
+        .await
+        {
+            self.message = Some(e);
+        }
+        self.reselect(selected);
+    }
+
+    fn selected(&self) -> Option<&DockerOutput> {
+        self.table.selected().and_then(|i| self.containers.get(i))
+    }
+
+    /// Keeps the cursor on the same container when rows move around.
+    fn reselect(&mut self, id: Option<String>) {
+        let position = id.and_then(|id| self.containers.iter().position(|d| d.id == id));
+        match position {
+            Some(i) => self.table.select(Some(i)),
+            None if self.containers.is_empty() => self.table.select(None),
+            None => {
+                let i = self.table.selected().unwrap_or_default();
+                self.table.select(Some(i.min(self.containers.len() - 1)));
+            }
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        if !self.containers.is_empty() {
+            self.table
+                .select(Some(index.min(self.containers.len() - 1)));
+            self.detail_scroll = 0;
+        }
+    }
+
+    /// Runs the action in the background so a slow stop doesn't freeze the UI. The
+    /// outcome shows up in the status bar; the event stream takes care of the row.
+    fn perform(&self, action: Action, d: DockerOutput) {
+        let client = self.client.clone();
+        let results = self.results.clone();
+        tokio::spawn(async move {
+            let message = match action.perform(&client, &d.id).await {
+                Ok(()) => format!("{} {}", action.past_tense(), display_name(&d)),
+                Err(e) => format!("failed to {} {}: {}", action.verb(), display_name(&d), e),
+            };
+            let _ = results.send(message).await;
+        });
+    }
+
+    fn request(&mut self, action: Action) {
+        let Some(d) = self.selected().cloned() else {
+            return;
+        };
+        if action.is_destructive() {
+            self.message = Some(format!(
+                "really {} {}? (y/N)",
+                action.verb(),
+                display_name(&d)
+            ));
+            self.pending = Some((action, d));
+        } else {
+            self.message = Some(format!("{} {}...", action.progressive(), display_name(&d)));
+            self.perform(action, d);
+        }
+    }
+
+    /// Returns false once the user asked to quit.
+    async fn handle_key(&mut self, code: KeyCode) -> bool {
+        self.message = None;
+        if let Some((action, d)) = self.pending.take() {
+            if code == KeyCode::Char('y') {
+                self.message = Some(format!("{} {}...", action.progressive(), display_name(&d)));
+                self.perform(action, d);
+            }
+            return true;
+        }
+
+        let current = self.table.selected().unwrap_or_default();
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Down | KeyCode::Char('j') => self.select(current + 1),
+            KeyCode::Up | KeyCode::Char('k') => self.select(current.saturating_sub(1)),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            KeyCode::Enter => {
+                self.show_detail = !self.show_detail;
+                self.detail_scroll = 0;
+            }
+            KeyCode::PageDown => self.detail_scroll = self.detail_scroll.saturating_add(10),
+            KeyCode::PageUp => self.detail_scroll = self.detail_scroll.saturating_sub(10),
+            KeyCode::Char('a') => {
+                self.args.all = !self.args.all;
+                self.refresh().await;
+            }
+            KeyCode::Char('r') => self.refresh().await,
+            KeyCode::Char('s') => self.request(Action::Start),
+            KeyCode::Char('x') => self.request(Action::Stop { time: None }),
+            KeyCode::Char('R') => self.request(Action::Restart { time: None }),
+            KeyCode::Char('K') => self.request(Action::Kill { signal: None }),
+            KeyCode::Char('D') => self.request(Action::Remove {
+                force: false,
+                volumes: false,
+            }),
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [list, detail] = if self.show_detail {
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main)
+        } else {
+            [main, Default::default()]
+        };
+
+        let columns = &self.args.columns;
+        let rows = convert_containers(&self.containers, !self.args.no_truncate);
+        let widths = columns.iter().map(|c| {
+            let longest = rows
+                .iter()
+                .map(|d| Span::raw(c.value(d)).width())
+                .max()
+                .unwrap_or_default();
+            Constraint::Length(longest.max(c.header().len()) as u16)
+        });
+        let table = Table::new(
+            rows.iter().map(|d| {
+                Row::new(columns.iter().map(|c| {
+                    let cell = Cell::from(c.value(d));
+                    if *c == Column::Health {
+                        cell.style(health_style(&self.args.theme, d.health))
+                    } else {
+                        cell
+                    }
+                }))
+                .style(state_style(&self.args.theme, &d.state))
+            }),
+            widths,
+        )
+        .header(
+            Row::new(columns.iter().map(|c| c.header()))
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(format!(
+            " containers ({}{}) ",
+            self.containers.len(),
+            if self.args.all { ", all" } else { "" }
+        )));
+        frame.render_stateful_widget(table, list, &mut self.table);
+
+        if self.show_detail
+            && let Some(d) = self.selected()
+        {
+            let json = serde_json::to_string_pretty(d).expect("Failed to serialize container");
+            let title = d.names.first().cloned().unwrap_or_else(|| d.id.clone());
+            let paragraph = Paragraph::new(json)
+                .wrap(Wrap { trim: false })
+                .scroll((self.detail_scroll, 0))
+                .block(Block::bordered().title(format!(" {} ", title)));
+            frame.render_widget(paragraph, detail);
+        }
+
+        let mode = if self.following {
+            "following events"
+        } else {
+            "polling"
+        };
+        let line = match &self.message {
+            Some(message) => Line::from(format!(" {}", message)),
+            None => Line::from(format!(
+                " q quit  ↑/↓ select  enter details  a all  r refresh  s start  x stop  R restart  K kill  D remove   [{}]",
+                mode
+            ))
+            .style(Style::default().add_modifier(Modifier::DIM)),
+        };
+        frame.render_widget(line, status);
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal, mut results: mpsc::Receiver<String>) {
+        let mut input = spawn_input();
+        let mut events = Some(subscribe_events(self.client));
+        let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut resync =
+            tokio::time::interval_at(Instant::now() + RESYNC_INTERVAL, RESYNC_INTERVAL);
+        resync.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            self.following = events.is_some();
+            terminal
+                .draw(|frame| self.draw(frame))
+                .expect("Failed to draw");
+
+            tokio::select! {
+                Some(event) = input.recv() => {
+                    if let Event::Key(key) = event
+                        && key.kind == KeyEventKind::Press
+                        && !self.handle_key(key.code).await
+                    {
+                        return;
+                    }
+                }
+                _ = ticker.tick() => {
+                    // Events keep the list current; polling only when they're gone.
+                    if events.is_none() {
+                        self.refresh().await;
+                    }
+                }
+                _ = resync.tick(), if events.is_some() => self.refresh().await,
+                Some(message) = results.recv() => self.message = Some(message),
+                event = next_event(&mut events) => match event {
+                    Some(id) => self.update(&id).await,
+                    None => events = None,
+                },
+            }
+        }
+    }
+}
+
+pub async fn run(client: &DockerClient, args: &Args) {
+    let (tx, rx) = mpsc::channel(16);
+    let mut app = App {
+        client,
+        args: args.clone(),
+        containers: Vec::new(),
+        table: TableState::default(),
+        show_detail: false,
+        detail_scroll: 0,
+        following: false,
+        pending: None,
+        message: None,
+        results: tx,
+    };
+
+    // Load before taking over the terminal, so daemon errors (e.g. a bad filter
+    // value) are still readable. Later errors end up in the status bar.
+    if let Err(e) = app.reload().await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+
+    let mut terminal = ratatui::init();
+    app.run(&mut terminal, rx).await;
+    ratatui::restore();
+}
@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use tabled::{Tabled, settings::object::Rows};
+
+use crate::{
+    client::DockerClient,
+    fetch_containers, host_name, human_size,
+    layout::{self, Fit, TableStyle},
+    output::{self, OutputFormat},
+    theme::Theme,
+};
+
+#[derive(Tabled, Serialize, Debug)]
+struct Volume {
+    name: String,
+    driver: String,
+    mountpoint: String,
+    size: String,
+    containers: String,
+    #[tabled(skip)]
+    orphaned: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct VolumeList {
+    #[serde(rename = "Volumes", default)]
+    volumes: Option<Vec<VolumeOutput>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct VolumeOutput {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Driver")]
+    driver: String,
+    #[serde(rename = "Mountpoint")]
+    mountpoint: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DiskUsage {
+    #[serde(rename = "Volumes", default)]
+    volumes: Option<Vec<VolumeUsage>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct VolumeUsage {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "UsageData", default)]
+    usage_data: Option<UsageData>,
+}
+
+#[derive(Deserialize, Debug)]
+struct UsageData {
+    /// -1 when the driver can't tell.
+    #[serde(rename = "Size")]
+    size: i64,
+}
+
+pub async fn run(
+    client: &DockerClient,
+    orphaned_only: bool,
+    truncate: bool,
+    format: OutputFormat,
+    theme: &Theme,
+    style: &TableStyle,
+) {
+    let list = client
+        .get("/volumes")
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json::<VolumeList>()
+        .await
+        .expect("Failed to parse JSON response (are you sure the Docker daemon is running?)");
+
+    let usage = client
+        .get("/system/df")
+        .query(&[("type", "volume")])
+        .send()
+        .await
+        .expect("Failed to send request")
+        .json::<DiskUsage>()
+        .await
+        .expect("Failed to parse JSON response");
+    let sizes: HashMap<String, i64> = usage
+        .volumes
+        .into_iter()
+        .flatten()
+        .filter_map(|v| Some((v.name, v.usage_data?.size)))
+        .collect();
+
+    // Stopped containers still hold on to their volumes, so look at all of them.
+    let mut references: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for d in fetch_containers(client, true, &[], false).await {
+        for m in &d.mounts {
+            if let (Some(name), "volume") = (&m.name, m.mount_type.as_str()) {
+                references
+                    .entry(name.clone())
+                    .or_default()
+                    .push(d.names.first().cloned().unwrap_or_default());
+            }
+        }
+    }
+
+    let mut volumes = Vec::new();
+    for v in list.volumes.into_iter().flatten() {
+        let containers = references.remove(&v.name).unwrap_or_default();
+        let orphaned = containers.is_empty();
+        if orphaned_only && !orphaned {
+            continue;
+        }
+
+        let size = match sizes.get(&v.name) {
+            Some(size) if *size >= 0 => human_size(*size),
+            _ => "N/A".to_string(),
+        };
+        volumes.push(Volume {
+            name: v.name,
+            driver: v.driver,
+            mountpoint: v.mountpoint,
+            size,
+            containers: if orphaned {
+                "(orphaned)".to_string()
+            } else {
+                containers.join(", ")
+            },
+            orphaned,
+        });
+    }
+    volumes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if format.is_structured() {
+        output::print_raw(&volumes, format);
+        return;
+    }
+
+    if format.is_report() {
+        let report = output::Report {
+            title: "Volumes".to_string(),
+            host: host_name(client).await,
+            headers: Volume::headers().into_iter().map(String::from).collect(),
+            records: volumes
+                .iter()
+                .map(|v| v.fields().into_iter().map(String::from).collect())
+                .collect(),
+            classes: volumes
+                .iter()
+                .map(|v| if v.orphaned { "orphaned" } else { "" }.to_string())
+                .collect(),
+        };
+        output::print_report(&report, format, style.stylesheet);
+        return;
+    }
+
+    if format != OutputFormat::Table {
+        output::print_records(
+            Volume::headers().into_iter().map(String::from).collect(),
+            volumes
+                .iter()
+                .map(|v| v.fields().into_iter().map(String::from).collect()),
+            format,
+            style.header,
+        );
+        return;
+    }
+
+    let fits = [
+        Fit::Ellipsize(1),
+        Fit::Ellipsize(1),
+        Fit::Ellipsize(2),
+        Fit::Fixed,
+        Fit::Wrap(2),
+    ];
+    let mut table = layout::table(&volumes, &fits, style, truncate);
+
+    if let Some(color) = &theme.orphaned {
+        for (i, v) in volumes.iter().enumerate() {
+            if v.orphaned {
+                table.modify(Rows::one(i + style.first_row()), color.clone());
+            }
+        }
+    }
+
+    println!("{}", table);
+}
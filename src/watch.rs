@@ -0,0 +1,274 @@
+use std::{io::Write, time::Duration};
+
+use serde::Deserialize;
+use tabled::settings::Color;
+use tokio::{
+    sync::mpsc,
+    time::{Instant, MissedTickBehavior},
+};
+
+use crate::{
+    Args, Column, DockerOutput, Health,
+    client::{DockerClient, JsonLines},
+    convert_containers, fetch_details, fetch_stats, load_containers, render, sort_containers,
+    theme::Theme,
+    try_fetch_containers, wants_details, wants_stats,
+};
+
+/// How often the whole list is re-queried while following the event stream, so the
+/// daemon's free-text status ("Up 5 minutes") doesn't go stale.
+pub const RESYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+const EVENTS: &[&str] = &[
+    "create",
+    "start",
+    "restart",
+    "die",
+    "destroy",
+    "pause",
+    "unpause",
+    "rename",
+    "health_status",
+];
+
+/// What happened to a row since the previous refresh in watch mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Unchanged,
+    Added,
+    Changed,
+    Removed,
+}
+
+impl Change {
+    pub fn marker(self) -> &'static str {
+        match self {
+            Change::Unchanged => "",
+            Change::Added => "+",
+            Change::Changed => "~",
+            Change::Removed => "-",
+        }
+    }
+
+    pub fn color(self, theme: &Theme) -> Option<Color> {
+        match self {
+            Change::Unchanged => None,
+            Change::Added => theme.added.clone(),
+            Change::Changed => theme.changed.clone(),
+            Change::Removed => theme.removed.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Event {
+    #[serde(rename = "Actor")]
+    actor: Actor,
+}
+
+#[derive(Deserialize, Debug)]
+struct Actor {
+    #[serde(rename = "ID")]
+    id: String,
+}
+
+/// Follows `/events` in the background and forwards the ids of containers that changed.
+/// The channel closes when the stream drops.
+pub fn subscribe_events(client: &DockerClient) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel(64);
+    let filters = serde_json::json!({ "type": ["container"], "event": EVENTS });
+    let request = client
+        .get("/events")
+        .query(&[("filters", filters.to_string())]);
+
+    tokio::spawn(async move {
+        let Ok(response) = request.send().await else {
+            return;
+        };
+        if !response.status().is_success() {
+            return;
+        }
+
+        let mut lines = JsonLines::new(response);
+        while let Some(event) = lines.next::<Event>().await {
+            if tx.send(event.actor.id).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    rx
+}
+
+pub async fn next_event(events: &mut Option<mpsc::Receiver<String>>) -> Option<String> {
+    match events {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Re-fetches a single container after an event and patches it into `current`, which
+/// is left alone if the daemon can't be reached.
+pub async fn update_container(
+    client: &DockerClient,
+    args: &Args,
+    columns: &[Column],
+    current: &mut Vec<DockerOutput>,
+    id: &str,
+) -> Result<(), String> {
+    let mut filters = args.filters.clone();
+    filters.push(("id".to_string(), id.to_string()));
+    let mut fetched =
+        try_fetch_containers(client, args.all, &filters, columns.contains(&Column::Size)).await?;
+    if wants_details(columns) {
+        fetch_details(client, &mut fetched).await;
+    }
+    if wants_stats(columns) {
+        fetch_stats(client, &mut fetched).await;
+    }
+
+    let position = current.iter().position(|d| d.id == id);
+    match (position, fetched.pop()) {
+        (Some(i), Some(d)) => current[i] = d,
+        (Some(i), None) => {
+            current.remove(i);
+        }
+        (None, Some(d)) => current.push(d),
+        (None, None) => {}
+    }
+    if let Some(key) = args.sort {
+        sort_containers(current, key, args.reverse);
+    }
+    Ok(())
+}
+
+fn draw(
+    args: &Args,
+    columns: &[Column],
+    interval: Duration,
+    previous: Option<&[DockerOutput]>,
+    current: &[DockerOutput],
+    following: bool,
+    error: Option<&str>,
+) {
+    let mut shown = current.to_vec();
+    let mut changes = Vec::new();
+    for d in current {
+        let change = match previous.iter().copied().flatten().find(|p| p.id == d.id) {
+            Some(p)
+                if p.state != d.state
+                    || Health::from_status(&p.status) != Health::from_status(&d.status) =>
+            {
+                Change::Changed
+            }
+            Some(_) => Change::Unchanged,
+            None if previous.is_some() => Change::Added,
+            None => Change::Unchanged,
+        };
+        changes.push(change);
+    }
+    // Containers that went away stay visible (greyed out) for one refresh.
+    for p in previous.iter().copied().flatten() {
+        if !current.iter().any(|d| d.id == p.id) {
+            shown.push(p.clone());
+            changes.push(Change::Removed);
+        }
+    }
+
+    let containers = convert_containers(&shown, !args.no_truncate);
+    let rendered = render(args, columns, &containers, Some(&changes));
+
+    let mode = if following {
+        "following events".to_string()
+    } else {
+        format!("every {:.1}s", interval.as_secs_f64())
+    };
+    let heading = match error {
+        Some(e) => format!("fancy-docker ({}, error: {})", mode, e),
+        None => format!("fancy-docker ({})", mode),
+    };
+    write_frame(&heading, &rendered);
+}
+
+/// Draws over the previous frame instead of clearing the screen first, which flickers.
+pub fn write_frame(heading: &str, body: &str) {
+    let mut frame = format!(
+        "\x1b[H{}    {}\x1b[K\n\x1b[K\n",
+        heading,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    for line in body.lines() {
+        frame.push_str(line);
+        frame.push_str("\x1b[K\n");
+    }
+    frame.push_str("\x1b[J");
+
+    let mut stdout = std::io::stdout().lock();
+    stdout
+        .write_all(frame.as_bytes())
+        .and_then(|_| stdout.flush())
+        .expect("Failed to write to stdout");
+}
+
+/// Keeps the table up to date from the daemon's event stream, redrawing every
+/// `interval`. Falls back to polling every `interval` if the stream drops. While the
+/// daemon can't be reached the last table stays up with the error above it, and the
+/// event stream is picked up again once it answers.
+pub async fn watch(client: &DockerClient, args: &Args, columns: &[Column], interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut resync = tokio::time::interval_at(Instant::now() + RESYNC_INTERVAL, RESYNC_INTERVAL);
+    resync.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut events = Some(subscribe_events(client));
+    let mut current = Vec::new();
+    let mut error = None;
+    let mut previous: Option<Vec<DockerOutput>> = None;
+    let mut reload = true;
+
+    loop {
+        if reload {
+            match load_containers(client, args, columns).await {
+                Ok(containers) => {
+                    current = containers;
+                    // The daemon is back, most likely after a restart that dropped the stream.
+                    if events.is_none() && error.is_some() {
+                        events = Some(subscribe_events(client));
+                    }
+                    error = None;
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+
+        draw(
+            args,
+            columns,
+            interval,
+            previous.as_deref(),
+            &current,
+            events.is_some(),
+            error.as_deref(),
+        );
+        previous = Some(current.clone());
+
+        reload = tokio::select! {
+            _ = ticker.tick() => events.is_none() || error.is_some(),
+            _ = resync.tick() => true,
+            event = next_event(&mut events) => match event {
+                Some(id) => {
+                    if let Err(e) =
+                        update_container(client, args, columns, &mut current, &id).await
+                    {
+                        error = Some(e);
+                    }
+                    false
+                }
+                None => {
+                    events = None;
+                    true
+                }
+            },
+        };
+    }
+}